@@ -0,0 +1,205 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::configs::Config;
+use crate::context::{self, Context};
+use crate::explain::format_duration;
+use crate::opt::BenchArgs;
+use crate::segments;
+use crate::table::{ignore_broken_pipe, print_table};
+
+#[derive(Debug, Serialize)]
+struct Stats {
+    min_ms: f64,
+    median_ms: f64,
+    p95_ms: f64,
+}
+
+impl Stats {
+    fn new(samples: &mut [Duration]) -> Self {
+        samples.sort();
+
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        Self {
+            min_ms: ms(samples.first().copied().unwrap_or_default()),
+            median_ms: ms(percentile(samples, 50.0)),
+            p95_ms: ms(percentile(samples, 95.0)),
+        }
+    }
+}
+
+// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::default();
+    }
+
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[test]
+fn test_percentile() {
+    let samples: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+
+    assert_eq!(percentile(&samples, 50.0), Duration::from_millis(10));
+    assert_eq!(percentile(&samples, 95.0), Duration::from_millis(19));
+    assert_eq!(percentile(&samples, 100.0), Duration::from_millis(20));
+    assert_eq!(percentile(&samples, 0.0), Duration::from_millis(1));
+    assert_eq!(percentile(&[], 50.0), Duration::default());
+}
+
+#[derive(Debug, Serialize)]
+struct SegmentReport {
+    line: String,
+    name: String,
+    #[serde(flatten)]
+    stats: Stats,
+    // The last error of the segment, which does not stop the other segments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    iterations: usize,
+    total: Stats,
+    context: Stats,
+    segments: Vec<SegmentReport>,
+}
+
+fn print_report(report: &Report) -> io::Result<()> {
+    let ms = |ms: f64| format_duration(Duration::from_secs_f64(ms / 1000.0));
+
+    let row = |line: &str, name: &str, stats: &Stats, error: Option<&str>| {
+        vec![
+            line.to_string(),
+            name.to_string(),
+            ms(stats.min_ms),
+            ms(stats.median_ms),
+            ms(stats.p95_ms),
+            error.unwrap_or_default().to_string(),
+        ]
+    };
+
+    let mut rows = vec![row("", "context", &report.context, None)];
+    rows.extend(report.segments.iter().map(|segment| {
+        row(
+            &segment.line,
+            &segment.name,
+            &segment.stats,
+            segment.error.as_deref(),
+        )
+    }));
+    rows.push(row("", "total", &report.total, None));
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    writeln!(out, "{} iterations", report.iterations)?;
+    writeln!(out)?;
+    print_table(&["LINE", "SEGMENT", "MIN", "MEDIAN", "P95", "ERROR"], &rows)
+}
+
+fn bench(args: BenchArgs) -> Result<Report> {
+    if let Some(dir) = &args.dir {
+        let dir = dir.canonicalize()?;
+        std::env::set_current_dir(&dir)?;
+        std::env::set_var("PWD", &dir);
+    }
+
+    let config = Arc::new(Config::load(
+        Config::config_path(),
+        &context::current_dir(),
+    )?);
+    let prompt = Arc::new(args.prompt);
+
+    let lines: Vec<(String, &[String])> = if prompt.right {
+        vec![("right".to_string(), &config.right_segments)]
+    } else if prompt.transient {
+        vec![("transient".to_string(), &config.transient_segments)]
+    } else if prompt.continuation {
+        vec![("continuation".to_string(), &config.continuation_segments)]
+    } else {
+        config
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(i, line)| ((i + 1).to_string(), line.as_slice()))
+            .collect()
+    };
+
+    let names: Vec<(&str, &str)> = lines
+        .iter()
+        .flat_map(|(line, names)| names.iter().map(move |name| (line.as_str(), name.as_str())))
+        .collect();
+
+    let segment_names: Vec<&str> = names.iter().map(|(_, name)| *name).collect();
+
+    let mut total_samples = Vec::with_capacity(args.iterations);
+    let mut context_samples = Vec::with_capacity(args.iterations);
+    let mut segment_samples = vec![Vec::with_capacity(args.iterations); names.len()];
+    let mut segment_errors = vec![None; names.len()];
+
+    for _ in 0..args.iterations {
+        let start = Instant::now();
+        let context = Arc::new(Context::new(Arc::clone(&prompt), Arc::clone(&config)));
+        context_samples.push(start.elapsed());
+
+        for ((built, samples), error) in segments::build_segments(&context, &segment_names)
+            .into_iter()
+            .zip(&mut segment_samples)
+            .zip(&mut segment_errors)
+        {
+            if let Err(err) = built.result {
+                *error = Some(err.to_string());
+            }
+            samples.push(built.elapsed);
+        }
+
+        total_samples.push(start.elapsed());
+    }
+
+    let segments = names
+        .iter()
+        .zip(&mut segment_samples)
+        .zip(segment_errors)
+        .map(|(((line, name), samples), error)| SegmentReport {
+            line: line.to_string(),
+            name: name.to_string(),
+            stats: Stats::new(samples),
+            error,
+        })
+        .collect();
+
+    Ok(Report {
+        iterations: args.iterations,
+        total: Stats::new(&mut total_samples),
+        context: Stats::new(&mut context_samples),
+        segments,
+    })
+}
+
+pub fn run(args: BenchArgs) {
+    let json = args.json;
+
+    let result = bench(args).and_then(|report| {
+        if json {
+            let json = serde_json::to_string_pretty(&report)?;
+            ignore_broken_pipe(writeln!(io::stdout(), "{}", json))?;
+        } else {
+            ignore_broken_pipe(print_report(&report))?;
+        }
+
+        Ok(())
+    });
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
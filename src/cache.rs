@@ -0,0 +1,134 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::opt::CacheArgs;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Record<T> {
+    key: Vec<String>,
+    value: T,
+}
+
+// $XDG_CACHE_HOME/almel, or the cache directory of the platform when it is unset
+pub fn cache_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("almel")),
+        _ => dirs::cache_dir().map(|dir| dir.join("almel")),
+    }
+}
+
+// Modification time of a file as a cache key component
+pub fn mtime<P: AsRef<Path>>(path: P) -> String {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos().to_string())
+        .unwrap_or_default()
+}
+
+// One file per name and id, holding the value computed for the latest key
+fn record_path(dir: &Path, name: &str, id: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    id.hash(&mut hasher);
+
+    dir.join(format!("{}-{:016x}.json", name, hasher.finish()))
+}
+
+fn load<T: DeserializeOwned>(path: &Path, key: &[String]) -> Option<T> {
+    let s = std::fs::read_to_string(path).ok()?;
+    let record: Record<T> = serde_json::from_str(&s).ok()?;
+
+    if record.key != key {
+        return None;
+    }
+
+    Some(record.value)
+}
+
+fn store<T: Serialize>(path: &Path, key: &[String], value: &T) -> Result<()> {
+    let record = Record {
+        key: key.to_vec(),
+        value,
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    // Write to a temporary file first so that concurrent prompts never read a partial record
+    let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&tmp_path, serde_json::to_string(&record)?)?;
+    std::fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+// Returns the value cached for `key`, or computes and caches it
+pub fn memoize<T, F>(name: &str, id: &str, key: &[String], f: F) -> Option<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Option<T>,
+{
+    let path = match cache_dir() {
+        Some(dir) => record_path(&dir, name, id),
+        None => return f(),
+    };
+
+    if let Some(value) = load(&path, key) {
+        return Some(value);
+    }
+
+    let value = f()?;
+    let _ = store(&path, key, &value); // Ignore error
+
+    Some(value)
+}
+
+#[test]
+fn test_load_store() {
+    let dir = std::env::temp_dir().join(format!("almel-cache-test-{}", std::process::id()));
+    let path = record_path(&dir, "test", "/repo/.git");
+    let key = vec!["a".to_string(), "1".to_string()];
+
+    assert_eq!(load::<String>(&path, &key), None);
+
+    store(&path, &key, &"value".to_string()).unwrap();
+    assert_eq!(load::<String>(&path, &key), Some("value".to_string()));
+    assert_eq!(load::<String>(&path, &["a".to_string()]), None);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn clear() -> Result<()> {
+    if let Some(dir) = cache_dir().filter(|dir| dir.exists()) {
+        std::fs::remove_dir_all(dir)?;
+    }
+
+    Ok(())
+}
+
+fn path() -> Result<()> {
+    if let Some(dir) = cache_dir() {
+        println!("{}", dir.display());
+    }
+
+    Ok(())
+}
+
+pub fn run(args: &CacheArgs) {
+    let result = match args {
+        CacheArgs::Clear => clear(),
+        CacheArgs::Path => path(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
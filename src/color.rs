@@ -0,0 +1,233 @@
+use ansi_term::{Color, Style};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+pub static POSSIBLE_COLOR_VALUES: &[&str] = &["auto", "always", "never", "truecolor", "256", "16"];
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            "truecolor" | "24bit" => Ok(Self::TrueColor),
+            "256" => Ok(Self::Ansi256),
+            "16" => Ok(Self::Ansi16),
+            _ => Err(format!("invalid color mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    pub fn from_mode(mode: ColorMode) -> Self {
+        match mode {
+            ColorMode::Auto => Self::detect(
+                std::env::var("NO_COLOR").ok().as_deref(),
+                std::env::var("COLORTERM").ok().as_deref(),
+                std::env::var("TERM").ok().as_deref(),
+            ),
+            ColorMode::Always | ColorMode::TrueColor => Self::TrueColor,
+            ColorMode::Never => Self::None,
+            ColorMode::Ansi256 => Self::Ansi256,
+            ColorMode::Ansi16 => Self::Ansi16,
+        }
+    }
+
+    fn detect(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if no_color.is_some_and(|v| !v.is_empty()) {
+            return Self::None;
+        }
+
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return Self::TrueColor;
+        }
+
+        match term {
+            // Assume a capable terminal (e.g. Windows Terminal)
+            None | Some("") => Self::TrueColor,
+            Some("dumb") => Self::None,
+            Some(term) if term.ends_with("-direct") => Self::TrueColor,
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            Some(_) => Self::Ansi16,
+        }
+    }
+
+    pub fn convert_color(&self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Ansi256, Color::RGB(r, g, b)) => Color::Fixed(rgb_to_fixed(r, g, b)),
+            (Self::Ansi16 | Self::None, Color::Fixed(n)) if n < 8 => basic_color(n),
+            (Self::Ansi16 | Self::None, Color::Fixed(n)) if n < 16 => basic_color(n - 8),
+            (Self::Ansi16 | Self::None, Color::Fixed(n)) => {
+                let (r, g, b) = fixed_to_rgb(n);
+                rgb_to_basic(r, g, b)
+            }
+            (Self::Ansi16 | Self::None, Color::RGB(r, g, b)) => rgb_to_basic(r, g, b),
+            (_, color) => color,
+        }
+    }
+
+    pub fn convert_style(&self, style: Style) -> Style {
+        Style {
+            foreground: style.foreground.map(|c| self.convert_color(c)),
+            background: style.background.map(|c| self.convert_color(c)),
+            ..style
+        }
+    }
+}
+
+#[test]
+fn test_color_depth_detect() {
+    assert_eq!(
+        ColorDepth::detect(Some("1"), Some("truecolor"), Some("xterm-256color")),
+        ColorDepth::None
+    );
+    assert_eq!(
+        ColorDepth::detect(Some(""), Some("truecolor"), Some("xterm")),
+        ColorDepth::TrueColor
+    );
+    assert_eq!(
+        ColorDepth::detect(None, None, Some("xterm-256color")),
+        ColorDepth::Ansi256
+    );
+    assert_eq!(
+        ColorDepth::detect(None, None, Some("xterm-direct")),
+        ColorDepth::TrueColor
+    );
+    assert_eq!(
+        ColorDepth::detect(None, None, Some("linux")),
+        ColorDepth::Ansi16
+    );
+    assert_eq!(
+        ColorDepth::detect(None, None, Some("dumb")),
+        ColorDepth::None
+    );
+    assert_eq!(ColorDepth::detect(None, None, None), ColorDepth::TrueColor);
+}
+
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 8] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Purple, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::White, (229, 229, 229)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).unsigned_abs().pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn basic_color(n: u8) -> Color {
+    BASIC_COLORS[usize::from(n)].0
+}
+
+fn fixed_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=7 => BASIC_COLORS[usize::from(n)].1,
+        8..=15 => {
+            let (r, g, b) = BASIC_COLORS[usize::from(n - 8)].1;
+            (
+                r.saturating_add(50),
+                g.saturating_add(50),
+                b.saturating_add(50),
+            )
+        }
+        16..=231 => {
+            let i = n - 16;
+            (
+                CUBE_LEVELS[usize::from(i / 36)],
+                CUBE_LEVELS[usize::from(i / 6 % 6)],
+                CUBE_LEVELS[usize::from(i % 6)],
+            )
+        }
+        232..=255 => {
+            let level = 8 + 10 * (n - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn rgb_to_fixed(r: u8, g: u8, b: u8) -> u8 {
+    let nearest_level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (i32::from(CUBE_LEVELS[i]) - i32::from(c)).abs())
+            .unwrap_or(0) as u8
+    };
+
+    let cube = 16 + 36 * nearest_level(r) + 6 * nearest_level(g) + nearest_level(b);
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance((r, g, b), fixed_to_rgb(gray)) < distance((r, g, b), fixed_to_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn rgb_to_basic(r: u8, g: u8, b: u8) -> Color {
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::White)
+}
+
+#[test]
+fn test_convert_color() {
+    let depth = ColorDepth::Ansi256;
+    assert_eq!(depth.convert_color(Color::RGB(0, 0, 0)), Color::Fixed(16));
+    assert_eq!(
+        depth.convert_color(Color::RGB(255, 255, 255)),
+        Color::Fixed(231)
+    );
+    assert_eq!(
+        depth.convert_color(Color::RGB(30, 144, 255)),
+        Color::Fixed(33)
+    );
+    assert_eq!(
+        depth.convert_color(Color::RGB(128, 128, 128)),
+        Color::Fixed(244)
+    );
+    assert_eq!(depth.convert_color(Color::Fixed(33)), Color::Fixed(33));
+    assert_eq!(depth.convert_color(Color::Red), Color::Red);
+
+    let depth = ColorDepth::Ansi16;
+    assert_eq!(depth.convert_color(Color::Fixed(1)), Color::Red);
+    assert_eq!(depth.convert_color(Color::Fixed(12)), Color::Blue);
+    assert_eq!(depth.convert_color(Color::Fixed(27)), Color::Blue);
+    assert_eq!(depth.convert_color(Color::Fixed(231)), Color::White);
+    assert_eq!(depth.convert_color(Color::RGB(250, 10, 10)), Color::Red);
+    assert_eq!(depth.convert_color(Color::Green), Color::Green);
+
+    let depth = ColorDepth::TrueColor;
+    assert_eq!(
+        depth.convert_color(Color::RGB(30, 144, 255)),
+        Color::RGB(30, 144, 255)
+    );
+}
@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use std::process::Command;
+
+use crate::configs::check::check_files;
+use crate::configs::{Config, DEFAULT_CONFIG_STR};
+use crate::context;
+use crate::opt::ConfigArgs;
+
+fn check() -> Result<()> {
+    let config_path = Config::config_path();
+    let paths = Config::layer_paths(&config_path, &context::current_dir());
+
+    let diagnostics = check_files(&paths);
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+
+    if !diagnostics.is_empty() {
+        return Err(anyhow!("Found {} problem(s)", diagnostics.len()));
+    }
+
+    println!("No problems found");
+    Ok(())
+}
+
+fn default() -> Result<()> {
+    print!("{}", DEFAULT_CONFIG_STR);
+
+    Ok(())
+}
+
+fn show() -> Result<()> {
+    let config = Config::load(Config::config_path(), &context::current_dir())?;
+    print!("{}", serde_yaml::to_string(&config)?);
+
+    Ok(())
+}
+
+fn path() -> Result<()> {
+    println!("{}", Config::config_path().display());
+
+    Ok(())
+}
+
+fn edit() -> Result<()> {
+    let config_path = Config::config_path();
+
+    if !config_path.exists() {
+        Config::save_default_config(&config_path)?;
+    }
+
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+    let mut editor_args = editor.split_whitespace();
+    let program = editor_args
+        .next()
+        .ok_or_else(|| anyhow!("$EDITOR is empty"))?;
+
+    let status = Command::new(program)
+        .args(editor_args)
+        .arg(&config_path)
+        .status()
+        .map_err(|err| anyhow!("Failed to run {}: {}", program, err))?;
+
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", program, status));
+    }
+
+    Ok(())
+}
+
+pub fn run(args: &ConfigArgs) {
+    let result = match args {
+        ConfigArgs::Check => check(),
+        ConfigArgs::Default => default(),
+        ConfigArgs::Show => show(),
+        ConfigArgs::Path => path(),
+        ConfigArgs::Edit => edit(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,303 @@
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+use crate::configs::status::ConfigCode;
+use crate::configs::theme::find_theme;
+use crate::configs::{Config, SegmentStyle};
+use crate::segments::SEGMENT_NAMES;
+
+// Key of a schema matching any key of the configuration
+const ANY_KEY: &str = "*";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+// YAML tree with the line numbers of keys and items
+#[derive(Debug)]
+enum Node {
+    Scalar(String),
+    Sequence(Vec<(usize, Node)>),
+    Mapping(Vec<(String, usize, Node)>),
+}
+
+enum Frame {
+    Sequence(usize, Vec<(usize, Node)>),
+    Mapping(usize, Vec<(String, usize, Node)>, Option<(String, usize)>),
+}
+
+#[derive(Default)]
+struct NodeBuilder {
+    stack: Vec<Frame>,
+    root: Option<Node>,
+}
+
+impl NodeBuilder {
+    fn push_node(&mut self, node: Node, line: usize) {
+        match self.stack.last_mut() {
+            None => {
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            }
+            Some(Frame::Sequence(_, items)) => items.push((line, node)),
+            Some(Frame::Mapping(_, entries, pending_key)) => match pending_key.take() {
+                Some((key, key_line)) => entries.push((key, key_line, node)),
+                None => {
+                    let key = match node {
+                        Node::Scalar(key) => key,
+                        _ => "?".to_string(),
+                    };
+                    *pending_key = Some((key, line));
+                }
+            },
+        }
+    }
+}
+
+impl MarkedEventReceiver for NodeBuilder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        let line = mark.line();
+
+        match ev {
+            Event::Scalar(s, ..) => self.push_node(Node::Scalar(s), line),
+            Event::Alias(_) => self.push_node(Node::Scalar(String::new()), line),
+            Event::SequenceStart(_) => self.stack.push(Frame::Sequence(line, Vec::new())),
+            Event::MappingStart(_) => self.stack.push(Frame::Mapping(line, Vec::new(), None)),
+            Event::SequenceEnd | Event::MappingEnd => match self.stack.pop() {
+                Some(Frame::Sequence(line, items)) => self.push_node(Node::Sequence(items), line),
+                Some(Frame::Mapping(line, entries, _)) => {
+                    self.push_node(Node::Mapping(entries), line)
+                }
+                None => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn check_keys(node: &Node, schema: &Value, path: &str, problems: &mut Vec<Problem>) {
+    let (entries, schema) = match (node, schema) {
+        (Node::Mapping(entries), Value::Mapping(schema)) => (entries, schema),
+        _ => return,
+    };
+
+    // Maps whose keys are chosen by the user, such as status.codes, check every entry
+    // against the same schema
+    let any_key = schema.get(&Value::String(ANY_KEY.to_string()));
+
+    for (key, line, value) in entries {
+        let key_path = join_path(path, key);
+
+        match any_key.or_else(|| schema.get(&Value::String(key.clone()))) {
+            Some(schema) => check_keys(value, schema, &key_path, problems),
+            None => problems.push(Problem {
+                line: Some(*line),
+                message: format!("Unknown key: {}", key_path),
+            }),
+        }
+    }
+}
+
+// Schema of the configuration, built from its defaults
+fn schema() -> Option<Value> {
+    let mut schema = serde_yaml::to_value(Config::default()).ok()?;
+
+    // Every field of a code is set so that it appears in the schema
+    let code = ConfigCode {
+        icon: Some(String::new()),
+        style: Some(SegmentStyle::default()),
+    };
+    let code_schema = serde_yaml::to_value(code).ok()?;
+
+    let codes = schema.get_mut("status")?.get_mut("codes")?;
+    *codes = Value::Mapping(
+        [(Value::String(ANY_KEY.to_string()), code_schema)]
+            .into_iter()
+            .collect(),
+    );
+
+    Some(schema)
+}
+
+fn check_segment_names(node: &Node, line: usize, problems: &mut Vec<Problem>) {
+    match node {
+        Node::Scalar(name) if !SEGMENT_NAMES.contains(&name.as_str()) => problems.push(Problem {
+            line: Some(line),
+            message: format!("Unknown segment: {}", name),
+        }),
+        Node::Scalar(_) => {}
+        Node::Sequence(items) => {
+            for (line, item) in items {
+                check_segment_names(item, *line, problems);
+            }
+        }
+        Node::Mapping(_) => {}
+    }
+}
+
+fn check_root(root: &Node, problems: &mut Vec<Problem>) {
+    let entries = match root {
+        Node::Mapping(entries) => entries,
+        _ => return,
+    };
+
+    for (key, line, value) in entries {
+        match (key.as_str(), value) {
+            ("theme", Node::Scalar(theme)) if find_theme(theme).is_none() => {
+                problems.push(Problem {
+                    line: Some(*line),
+                    message: format!("Unknown theme: {}", theme),
+                })
+            }
+            (
+                "segments" | "right_segments" | "transient_segments" | "continuation_segments",
+                value,
+            ) => check_segment_names(value, *line, problems),
+            _ => {}
+        }
+    }
+}
+
+fn deserialize_problem(s: &str) -> Option<Problem> {
+    let err = serde_yaml::from_str::<Config>(s).err()?;
+    let location = err.location();
+
+    let mut message = err.to_string();
+    if let Some(location) = &location {
+        let suffix = format!(" at line {} column {}", location.line(), location.column());
+        if let Some(stripped) = message.strip_suffix(&suffix) {
+            message = stripped.to_string();
+        }
+    }
+
+    Some(Problem {
+        line: location.map(|l| l.line()),
+        message,
+    })
+}
+
+// Lists every problem found in a configuration file
+pub fn check_str(s: &str) -> Vec<Problem> {
+    if s.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mut builder = NodeBuilder::default();
+    if let Err(err) = Parser::new(s.chars()).load(&mut builder, false) {
+        return vec![Problem {
+            line: Some(err.marker().line()),
+            message: err.to_string(),
+        }];
+    }
+
+    let mut problems: Vec<Problem> = deserialize_problem(s).into_iter().collect();
+
+    if let Some(root) = &builder.root {
+        if let Some(schema) = schema() {
+            check_keys(root, &schema, "", &mut problems);
+        }
+        check_root(root, &mut problems);
+    }
+
+    problems.sort_by_key(|p| p.line);
+    problems
+}
+
+// Lists every problem found in the existing configuration files as `path:line: message`
+pub fn check_files(paths: &[PathBuf]) -> Vec<String> {
+    let mut diagnostics = Vec::new();
+
+    for path in paths.iter().filter(|path| path.exists()) {
+        let s = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(err) => {
+                diagnostics.push(format!("{}: {}", path.display(), err));
+                continue;
+            }
+        };
+
+        for problem in check_str(&s) {
+            diagnostics.push(format_problem(path, &problem));
+        }
+    }
+
+    diagnostics
+}
+
+fn format_problem(path: &Path, problem: &Problem) -> String {
+    match problem.line {
+        Some(line) => format!("{}:{}: {}", path.display(), line, problem.message),
+        None => format!("{}: {}", path.display(), problem.message),
+    }
+}
+
+#[test]
+fn test_check_str() {
+    let s = "\
+theme: nope
+os:
+  linux:
+    style:
+      backgroud: Red
+segments:
+  - - os
+    - dirctory
+right_segments: [time, clock]
+";
+
+    let actual = check_str(s);
+    let expected = vec![
+        Problem {
+            line: Some(1),
+            message: "Unknown theme: nope".to_string(),
+        },
+        Problem {
+            line: Some(5),
+            message: "Unknown key: os.linux.style.backgroud".to_string(),
+        },
+        Problem {
+            line: Some(8),
+            message: "Unknown segment: dirctory".to_string(),
+        },
+        Problem {
+            line: Some(9),
+            message: "Unknown segment: clock".to_string(),
+        },
+    ];
+    assert_eq!(actual, expected);
+
+    let actual = check_str("user:\n  style:\n    background: \"#12\"\n");
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0].line, Some(3));
+    assert!(actual[0]
+        .message
+        .starts_with("user.style.background: invalid color"));
+
+    let actual = check_str("status:\n  codes:\n    130:\n      icon: x\n");
+    assert_eq!(actual, vec![]);
+
+    let actual =
+        check_str("status:\n  codes:\n    130:\n      icn: x\n      style:\n        bold: true\n");
+    assert_eq!(
+        actual,
+        vec![Problem {
+            line: Some(4),
+            message: "Unknown key: status.codes.130.icn".to_string(),
+        }]
+    );
+
+    assert_eq!(check_str(super::DEFAULT_CONFIG_STR), vec![]);
+}
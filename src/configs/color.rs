@@ -0,0 +1,324 @@
+use ansi_term::Color;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fmt;
+
+static NAMED_COLORS: &[(&str, Color)] = &[
+    // ANSI colors
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("purple", Color::Purple),
+    ("magenta", Color::Purple),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+    // CSS colors
+    ("aliceblue", Color::RGB(240, 248, 255)),
+    ("antiquewhite", Color::RGB(250, 235, 215)),
+    ("aqua", Color::RGB(0, 255, 255)),
+    ("aquamarine", Color::RGB(127, 255, 212)),
+    ("azure", Color::RGB(240, 255, 255)),
+    ("beige", Color::RGB(245, 245, 220)),
+    ("bisque", Color::RGB(255, 228, 196)),
+    ("blanchedalmond", Color::RGB(255, 235, 205)),
+    ("blueviolet", Color::RGB(138, 43, 226)),
+    ("brown", Color::RGB(165, 42, 42)),
+    ("burlywood", Color::RGB(222, 184, 135)),
+    ("cadetblue", Color::RGB(95, 158, 160)),
+    ("chartreuse", Color::RGB(127, 255, 0)),
+    ("chocolate", Color::RGB(210, 105, 30)),
+    ("coral", Color::RGB(255, 127, 80)),
+    ("cornflowerblue", Color::RGB(100, 149, 237)),
+    ("cornsilk", Color::RGB(255, 248, 220)),
+    ("crimson", Color::RGB(220, 20, 60)),
+    ("darkblue", Color::RGB(0, 0, 139)),
+    ("darkcyan", Color::RGB(0, 139, 139)),
+    ("darkgoldenrod", Color::RGB(184, 134, 11)),
+    ("darkgray", Color::RGB(169, 169, 169)),
+    ("darkgreen", Color::RGB(0, 100, 0)),
+    ("darkgrey", Color::RGB(169, 169, 169)),
+    ("darkkhaki", Color::RGB(189, 183, 107)),
+    ("darkmagenta", Color::RGB(139, 0, 139)),
+    ("darkolivegreen", Color::RGB(85, 107, 47)),
+    ("darkorange", Color::RGB(255, 140, 0)),
+    ("darkorchid", Color::RGB(153, 50, 204)),
+    ("darkred", Color::RGB(139, 0, 0)),
+    ("darksalmon", Color::RGB(233, 150, 122)),
+    ("darkseagreen", Color::RGB(143, 188, 143)),
+    ("darkslateblue", Color::RGB(72, 61, 139)),
+    ("darkslategray", Color::RGB(47, 79, 79)),
+    ("darkslategrey", Color::RGB(47, 79, 79)),
+    ("darkturquoise", Color::RGB(0, 206, 209)),
+    ("darkviolet", Color::RGB(148, 0, 211)),
+    ("deeppink", Color::RGB(255, 20, 147)),
+    ("deepskyblue", Color::RGB(0, 191, 255)),
+    ("dimgray", Color::RGB(105, 105, 105)),
+    ("dimgrey", Color::RGB(105, 105, 105)),
+    ("dodgerblue", Color::RGB(30, 144, 255)),
+    ("firebrick", Color::RGB(178, 34, 34)),
+    ("floralwhite", Color::RGB(255, 250, 240)),
+    ("forestgreen", Color::RGB(34, 139, 34)),
+    ("fuchsia", Color::RGB(255, 0, 255)),
+    ("gainsboro", Color::RGB(220, 220, 220)),
+    ("ghostwhite", Color::RGB(248, 248, 255)),
+    ("gold", Color::RGB(255, 215, 0)),
+    ("goldenrod", Color::RGB(218, 165, 32)),
+    ("gray", Color::RGB(128, 128, 128)),
+    ("grey", Color::RGB(128, 128, 128)),
+    ("greenyellow", Color::RGB(173, 255, 47)),
+    ("honeydew", Color::RGB(240, 255, 240)),
+    ("hotpink", Color::RGB(255, 105, 180)),
+    ("indianred", Color::RGB(205, 92, 92)),
+    ("indigo", Color::RGB(75, 0, 130)),
+    ("ivory", Color::RGB(255, 255, 240)),
+    ("khaki", Color::RGB(240, 230, 140)),
+    ("lavender", Color::RGB(230, 230, 250)),
+    ("lavenderblush", Color::RGB(255, 240, 245)),
+    ("lawngreen", Color::RGB(124, 252, 0)),
+    ("lemonchiffon", Color::RGB(255, 250, 205)),
+    ("lightblue", Color::RGB(173, 216, 230)),
+    ("lightcoral", Color::RGB(240, 128, 128)),
+    ("lightcyan", Color::RGB(224, 255, 255)),
+    ("lightgoldenrodyellow", Color::RGB(250, 250, 210)),
+    ("lightgray", Color::RGB(211, 211, 211)),
+    ("lightgreen", Color::RGB(144, 238, 144)),
+    ("lightgrey", Color::RGB(211, 211, 211)),
+    ("lightpink", Color::RGB(255, 182, 193)),
+    ("lightsalmon", Color::RGB(255, 160, 122)),
+    ("lightseagreen", Color::RGB(32, 178, 170)),
+    ("lightskyblue", Color::RGB(135, 206, 250)),
+    ("lightslategray", Color::RGB(119, 136, 153)),
+    ("lightslategrey", Color::RGB(119, 136, 153)),
+    ("lightsteelblue", Color::RGB(176, 196, 222)),
+    ("lightyellow", Color::RGB(255, 255, 224)),
+    ("lime", Color::RGB(0, 255, 0)),
+    ("limegreen", Color::RGB(50, 205, 50)),
+    ("linen", Color::RGB(250, 240, 230)),
+    ("maroon", Color::RGB(128, 0, 0)),
+    ("mediumaquamarine", Color::RGB(102, 205, 170)),
+    ("mediumblue", Color::RGB(0, 0, 205)),
+    ("mediumorchid", Color::RGB(186, 85, 211)),
+    ("mediumpurple", Color::RGB(147, 112, 219)),
+    ("mediumseagreen", Color::RGB(60, 179, 113)),
+    ("mediumslateblue", Color::RGB(123, 104, 238)),
+    ("mediumspringgreen", Color::RGB(0, 250, 154)),
+    ("mediumturquoise", Color::RGB(72, 209, 204)),
+    ("mediumvioletred", Color::RGB(199, 21, 133)),
+    ("midnightblue", Color::RGB(25, 25, 112)),
+    ("mintcream", Color::RGB(245, 255, 250)),
+    ("mistyrose", Color::RGB(255, 228, 225)),
+    ("moccasin", Color::RGB(255, 228, 181)),
+    ("navajowhite", Color::RGB(255, 222, 173)),
+    ("navy", Color::RGB(0, 0, 128)),
+    ("oldlace", Color::RGB(253, 245, 230)),
+    ("olive", Color::RGB(128, 128, 0)),
+    ("olivedrab", Color::RGB(107, 142, 35)),
+    ("orange", Color::RGB(255, 165, 0)),
+    ("orangered", Color::RGB(255, 69, 0)),
+    ("orchid", Color::RGB(218, 112, 214)),
+    ("palegoldenrod", Color::RGB(238, 232, 170)),
+    ("palegreen", Color::RGB(152, 251, 152)),
+    ("paleturquoise", Color::RGB(175, 238, 238)),
+    ("palevioletred", Color::RGB(219, 112, 147)),
+    ("papayawhip", Color::RGB(255, 239, 213)),
+    ("peachpuff", Color::RGB(255, 218, 185)),
+    ("peru", Color::RGB(205, 133, 63)),
+    ("pink", Color::RGB(255, 192, 203)),
+    ("plum", Color::RGB(221, 160, 221)),
+    ("powderblue", Color::RGB(176, 224, 230)),
+    ("rebeccapurple", Color::RGB(102, 51, 153)),
+    ("rosybrown", Color::RGB(188, 143, 143)),
+    ("royalblue", Color::RGB(65, 105, 225)),
+    ("saddlebrown", Color::RGB(139, 69, 19)),
+    ("salmon", Color::RGB(250, 128, 114)),
+    ("sandybrown", Color::RGB(244, 164, 96)),
+    ("seagreen", Color::RGB(46, 139, 87)),
+    ("seashell", Color::RGB(255, 245, 238)),
+    ("sienna", Color::RGB(160, 82, 45)),
+    ("silver", Color::RGB(192, 192, 192)),
+    ("skyblue", Color::RGB(135, 206, 235)),
+    ("slateblue", Color::RGB(106, 90, 205)),
+    ("slategray", Color::RGB(112, 128, 144)),
+    ("slategrey", Color::RGB(112, 128, 144)),
+    ("snow", Color::RGB(255, 250, 250)),
+    ("springgreen", Color::RGB(0, 255, 127)),
+    ("steelblue", Color::RGB(70, 130, 180)),
+    ("tan", Color::RGB(210, 180, 140)),
+    ("teal", Color::RGB(0, 128, 128)),
+    ("thistle", Color::RGB(216, 191, 216)),
+    ("tomato", Color::RGB(255, 99, 71)),
+    ("turquoise", Color::RGB(64, 224, 208)),
+    ("violet", Color::RGB(238, 130, 238)),
+    ("wheat", Color::RGB(245, 222, 179)),
+    ("whitesmoke", Color::RGB(245, 245, 245)),
+    ("yellowgreen", Color::RGB(154, 205, 50)),
+];
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match hex.len() {
+        3 => {
+            let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+            Some(Color::RGB(digit(0)?, digit(1)?, digit(2)?))
+        }
+        6 => {
+            let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            Some(Color::RGB(byte(0)?, byte(2)?, byte(4)?))
+        }
+        _ => None,
+    }
+}
+
+fn parse_rgb(args: &str) -> Option<Color> {
+    let components = args
+        .split(',')
+        .map(|c| c.trim().parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match components[..] {
+        [r, g, b] => Some(Color::RGB(r, g, b)),
+        _ => None,
+    }
+}
+
+pub fn parse_color(s: &str) -> Option<Color> {
+    let s = s.trim();
+
+    if let Some(hex) = s.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    if let Some(args) = s
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return parse_rgb(args);
+    }
+
+    if let Ok(index) = s.parse::<u8>() {
+        return Some(Color::Fixed(index));
+    }
+
+    let name = s.to_ascii_lowercase().replace(['-', '_', ' '], "");
+    NAMED_COLORS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, color)| *color)
+}
+
+#[test]
+fn test_parse_color() {
+    assert_eq!(parse_color("#1e90ff"), Some(Color::RGB(30, 144, 255)));
+    assert_eq!(parse_color("#1E90FF"), Some(Color::RGB(30, 144, 255)));
+    assert_eq!(parse_color("#f80"), Some(Color::RGB(255, 136, 0)));
+    assert_eq!(
+        parse_color("rgb(30,144,255)"),
+        Some(Color::RGB(30, 144, 255))
+    );
+    assert_eq!(
+        parse_color("rgb( 30, 144, 255 )"),
+        Some(Color::RGB(30, 144, 255))
+    );
+    assert_eq!(parse_color("33"), Some(Color::Fixed(33)));
+    assert_eq!(parse_color("White"), Some(Color::White));
+    assert_eq!(parse_color("magenta"), Some(Color::Purple));
+    assert_eq!(parse_color("DodgerBlue"), Some(Color::RGB(30, 144, 255)));
+    assert_eq!(parse_color("dark-orange"), Some(Color::RGB(255, 140, 0)));
+
+    assert_eq!(parse_color("#1e90f"), None);
+    assert_eq!(parse_color("#gggggg"), None);
+    assert_eq!(parse_color("rgb(30,144)"), None);
+    assert_eq!(parse_color("rgb(30,144,256)"), None);
+    assert_eq!(parse_color("256"), None);
+    assert_eq!(parse_color("unknown"), None);
+}
+
+const EXPECTED: &str = "a color name, \"#rrggbb\", \"rgb(r, g, b)\" or a 256-color index";
+
+struct ColorVisitor;
+
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(EXPECTED)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Color, E> {
+        parse_color(s)
+            .ok_or_else(|| E::custom(format!("invalid color `{}`, expected {}", s, EXPECTED)))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Color, E> {
+        u8::try_from(n)
+            .map(Color::Fixed)
+            .map_err(|_| E::custom(format!("invalid color index `{}`, expected 0-255", n)))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Color, E> {
+        u8::try_from(n)
+            .map(Color::Fixed)
+            .map_err(|_| E::custom(format!("invalid color index `{}`, expected 0-255", n)))
+    }
+
+    // `{ Fixed: 33 }` and `{ RGB: [30, 144, 255] }`
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Color, A::Error> {
+        Color::deserialize(de::value::MapAccessDeserializer::new(map))
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    deserializer.deserialize_any(ColorVisitor)
+}
+
+pub fn format_color(color: &Color) -> String {
+    match color {
+        Color::Black => "Black".to_string(),
+        Color::Red => "Red".to_string(),
+        Color::Green => "Green".to_string(),
+        Color::Yellow => "Yellow".to_string(),
+        Color::Blue => "Blue".to_string(),
+        Color::Purple => "Purple".to_string(),
+        Color::Cyan => "Cyan".to_string(),
+        Color::White => "White".to_string(),
+        Color::Fixed(index) => index.to_string(),
+        Color::RGB(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
+pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+    match color {
+        Color::Fixed(index) => serializer.serialize_u8(*index),
+        _ => serializer.serialize_str(&format_color(color)),
+    }
+}
+
+pub mod option {
+    use ansi_term::Color;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "super")] Color);
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color>, D::Error> {
+        let color = Option::<Wrapper>::deserialize(deserializer)?;
+        Ok(color.map(|Wrapper(color)| color))
+    }
+
+    pub fn serialize<S: Serializer>(
+        color: &Option<Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(color) => super::serialize(color, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
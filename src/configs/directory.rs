@@ -19,6 +19,9 @@ pub struct Config {
 
     #[serde(default)]
     pub aliases: Vec<ConfigAlias>,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 impl Config {
     fn default_home() -> String {
@@ -33,6 +36,7 @@ impl Default for Config {
             home: Self::default_home(),
             shrink: Default::default(),
             aliases: Vec::new(),
+            timeout_ms: None,
         }
     }
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::default::Default;
 
-use crate::configs::SegmentStyle;
+use crate::configs::{IconSet, SegmentStyle};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -28,6 +28,12 @@ pub struct Config {
 
     #[serde(default = "Config::default_commit_hash_len")]
     pub commit_hash_len: usize,
+
+    #[serde(default)]
+    pub cache: bool,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 impl Config {
     fn default_display_master() -> bool {
@@ -39,6 +45,46 @@ impl Config {
     fn default_commit_hash_len() -> usize {
         6
     }
+
+    pub fn apply_icon_set(&mut self, icon_set: IconSet) {
+        let icons = &mut self.icons;
+
+        icon_set.replace(
+            &mut icons.branch,
+            &ConfigIcons::default_branch(),
+            "⎇",
+            "git",
+        );
+        icon_set.replace(&mut icons.tag, &ConfigIcons::default_tag(), "⚑", "tag");
+        icon_set.replace(&mut icons.commit, &ConfigIcons::default_commit(), "●", "@");
+        icon_set.replace(
+            &mut icons.modified,
+            &ConfigIcons::default_modified(),
+            "…",
+            "*",
+        );
+        icon_set.replace(&mut icons.added, &ConfigIcons::default_added(), "+", "+");
+        icon_set.replace(
+            &mut icons.deleted,
+            &ConfigIcons::default_deleted(),
+            "-",
+            "-",
+        );
+        icon_set.replace(
+            &mut icons.added_deleted,
+            &ConfigIcons::default_added_deleted(),
+            "±",
+            "+-",
+        );
+        icon_set.replace(
+            &mut icons.conflicted,
+            &ConfigIcons::default_conflicted(),
+            "✖",
+            "!",
+        );
+        icon_set.replace(&mut icons.behind, &ConfigIcons::default_behind(), "↓", "v");
+        icon_set.replace(&mut icons.ahead, &ConfigIcons::default_ahead(), "↑", "^");
+    }
 }
 impl Default for Config {
     fn default() -> Self {
@@ -51,6 +97,8 @@ impl Default for Config {
             display_master: Self::default_display_master(),
             display_tag: Self::default_display_tag(),
             commit_hash_len: Self::default_commit_hash_len(),
+            cache: false,
+            timeout_ms: None,
         }
     }
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::default::Default;
 
-use crate::configs::SegmentStyle;
+use crate::configs::{IconSet, SegmentStyle};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -10,17 +10,29 @@ pub struct Config {
 
     #[serde(default = "Config::default_icon")]
     pub icon: String,
+
+    #[serde(default)]
+    pub cache: bool,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 impl Config {
     fn default_icon() -> String {
         "\u{f2c0}".to_string() // nf-fa-user_o
     }
+
+    pub fn apply_icon_set(&mut self, icon_set: IconSet) {
+        icon_set.replace(&mut self.icon, &Self::default_icon(), "☺", "@");
+    }
 }
 impl Default for Config {
     fn default() -> Self {
         Self {
             style: Default::default(),
             icon: Self::default_icon(),
+            cache: false,
+            timeout_ms: None,
         }
     }
 }
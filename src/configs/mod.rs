@@ -1,3 +1,5 @@
+pub mod check;
+pub mod color;
 pub mod directory;
 pub mod duration;
 pub mod git_repo;
@@ -5,26 +7,54 @@ pub mod git_user;
 pub mod os;
 pub mod shell;
 pub mod status;
+pub mod theme;
 pub mod time;
 pub mod user;
 pub mod venv;
 
-use ansi_term::Color;
-use anyhow::Result;
+use ansi_term::{Color, Style};
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
 use std::default::Default;
 use std::fs::{create_dir_all, File};
-use std::io::prelude::Write; // File#write_all
+use std::io::prelude::{Read, Write}; // File#read_to_string, File#write_all
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
 
 // SegmentStyle
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SegmentStyle {
-    #[serde(default = "SegmentStyle::default_foreground")]
+    #[serde(default = "SegmentStyle::default_foreground", with = "color")]
     pub foreground: Color,
 
-    #[serde(default = "SegmentStyle::default_background")]
+    #[serde(default = "SegmentStyle::default_background", with = "color")]
     pub background: Color,
+
+    #[serde(default)]
+    pub bold: bool,
+
+    #[serde(default)]
+    pub dimmed: bool,
+
+    #[serde(default)]
+    pub italic: bool,
+
+    #[serde(default)]
+    pub underline: bool,
+
+    #[serde(default)]
+    pub blink: bool,
+
+    #[serde(default)]
+    pub reverse: bool,
+
+    #[serde(default)]
+    pub strikethrough: bool,
+
+    #[serde(default)]
+    pub icon: IconStyle,
 }
 impl SegmentStyle {
     fn default_foreground() -> Color {
@@ -33,16 +63,110 @@ impl SegmentStyle {
     fn default_background() -> Color {
         Color::Black
     }
+
+    pub fn ansi_style(&self) -> Style {
+        Style {
+            foreground: Some(self.foreground),
+            background: Some(self.background),
+            is_bold: self.bold,
+            is_dimmed: self.dimmed,
+            is_italic: self.italic,
+            is_underline: self.underline,
+            is_blink: self.blink,
+            is_reverse: self.reverse,
+            is_hidden: false,
+            is_strikethrough: self.strikethrough,
+        }
+    }
+
+    pub fn icon_ansi_style(&self) -> Style {
+        let icon = &self.icon;
+        let style = self.ansi_style();
+
+        Style {
+            foreground: icon.foreground.or(style.foreground),
+            is_bold: icon.bold.unwrap_or(style.is_bold),
+            is_dimmed: icon.dimmed.unwrap_or(style.is_dimmed),
+            is_italic: icon.italic.unwrap_or(style.is_italic),
+            is_underline: icon.underline.unwrap_or(style.is_underline),
+            is_blink: icon.blink.unwrap_or(style.is_blink),
+            is_reverse: icon.reverse.unwrap_or(style.is_reverse),
+            is_strikethrough: icon.strikethrough.unwrap_or(style.is_strikethrough),
+            ..style
+        }
+    }
 }
 impl Default for SegmentStyle {
     fn default() -> Self {
         Self {
             foreground: Self::default_foreground(),
             background: Self::default_background(),
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+            blink: false,
+            reverse: false,
+            strikethrough: false,
+            icon: Default::default(),
         }
     }
 }
 
+#[test]
+fn test_segment_style_ansi_style() {
+    let style = SegmentStyle {
+        foreground: Color::Red,
+        background: Color::Blue,
+        bold: true,
+        underline: true,
+        icon: IconStyle {
+            foreground: Some(Color::Yellow),
+            bold: Some(false),
+            italic: Some(true),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    assert_eq!(
+        style.ansi_style(),
+        Color::Red.on(Color::Blue).bold().underline()
+    );
+    assert_eq!(
+        style.icon_ansi_style(),
+        Color::Yellow.on(Color::Blue).italic().underline()
+    );
+}
+
+// Overrides of SegmentStyle applied to the icon of a segment
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IconStyle {
+    #[serde(default, with = "color::option")]
+    pub foreground: Option<Color>,
+
+    #[serde(default)]
+    pub bold: Option<bool>,
+
+    #[serde(default)]
+    pub dimmed: Option<bool>,
+
+    #[serde(default)]
+    pub italic: Option<bool>,
+
+    #[serde(default)]
+    pub underline: Option<bool>,
+
+    #[serde(default)]
+    pub blink: Option<bool>,
+
+    #[serde(default)]
+    pub reverse: Option<bool>,
+
+    #[serde(default)]
+    pub strikethrough: Option<bool>,
+}
+
 // Separators
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SegmentSeparators {
@@ -51,6 +175,12 @@ pub struct SegmentSeparators {
 
     #[serde(default = "SegmentSeparators::default_left_wire")]
     pub left_wire: String,
+
+    #[serde(default = "SegmentSeparators::default_right_solid")]
+    pub right_solid: String,
+
+    #[serde(default = "SegmentSeparators::default_right_wire")]
+    pub right_wire: String,
 }
 impl SegmentSeparators {
     fn default_left_solid() -> String {
@@ -59,18 +189,98 @@ impl SegmentSeparators {
     fn default_left_wire() -> String {
         "\u{e0b1}".to_string() // nf-pl-left_soft_divider
     }
+    fn default_right_solid() -> String {
+        "\u{e0b2}".to_string() // nf-pl-right_hard_divider
+    }
+    fn default_right_wire() -> String {
+        "\u{e0b3}".to_string() // nf-pl-right_soft_divider
+    }
+}
+impl SegmentSeparators {
+    fn apply_icon_set(&mut self, icon_set: IconSet) {
+        icon_set.replace(&mut self.left_solid, &Self::default_left_solid(), "▶", ">");
+        icon_set.replace(&mut self.left_wire, &Self::default_left_wire(), "❯", "|");
+        icon_set.replace(
+            &mut self.right_solid,
+            &Self::default_right_solid(),
+            "◀",
+            "<",
+        );
+        icon_set.replace(&mut self.right_wire, &Self::default_right_wire(), "❮", "|");
+    }
 }
 impl Default for SegmentSeparators {
     fn default() -> Self {
         Self {
             left_solid: Self::default_left_solid(),
             left_wire: Self::default_left_wire(),
+            right_solid: Self::default_right_solid(),
+            right_wire: Self::default_right_wire(),
         }
     }
 }
 
+// IconSet
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconSet {
+    #[default]
+    NerdFont,
+    Unicode,
+    Ascii,
+}
+impl IconSet {
+    // Replaces the icon with the one of this icon set if it is still the Nerd Font default
+    pub fn replace(&self, icon: &mut String, nerdfont: &str, unicode: &str, ascii: &str) {
+        if icon != nerdfont {
+            return;
+        }
+
+        match self {
+            Self::NerdFont => {}
+            Self::Unicode => *icon = unicode.to_string(),
+            Self::Ascii => *icon = ascii.to_string(),
+        }
+    }
+}
+impl FromStr for IconSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "nerdfont" => Ok(Self::NerdFont),
+            "unicode" => Ok(Self::Unicode),
+            "ascii" => Ok(Self::Ascii),
+            _ => Err(anyhow!("Unknown icon set: {}", s)),
+        }
+    }
+}
+
+#[test]
+fn test_icon_set_replace() {
+    let mut icon = "\u{e0b0}".to_string();
+    IconSet::NerdFont.replace(&mut icon, "\u{e0b0}", "▶", ">");
+    assert_eq!(icon, "\u{e0b0}");
+
+    IconSet::Ascii.replace(&mut icon, "\u{e0b0}", "▶", ">");
+    assert_eq!(icon, ">");
+
+    let mut icon = "custom".to_string();
+    IconSet::Unicode.replace(&mut icon, "\u{e0b0}", "▶", ">");
+    assert_eq!(icon, "custom");
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    pub strict: bool,
+
+    #[serde(default = "Config::default_theme")]
+    pub theme: String,
+
+    #[serde(default)]
+    pub icon_set: IconSet,
+
     #[serde(default)]
     pub os: os::Config,
 
@@ -106,8 +316,32 @@ pub struct Config {
 
     #[serde(default = "Config::default_segments")]
     pub segments: Vec<Vec<String>>,
+
+    #[serde(default)]
+    pub right_segments: Vec<String>,
+
+    #[serde(default)]
+    pub transient_segments: Vec<String>,
+
+    #[serde(default)]
+    pub continuation_segments: Vec<String>,
+
+    #[serde(default)]
+    pub timeout_ms: u64,
+
+    #[serde(default = "Config::default_timeout_placeholder")]
+    pub timeout_placeholder: String,
+
+    #[serde(default)]
+    pub timeout_style: SegmentStyle,
 }
 impl Config {
+    fn default_theme() -> String {
+        theme::DEFAULT_THEME.to_string()
+    }
+    fn default_timeout_placeholder() -> String {
+        "\u{2026}".to_string() // …
+    }
     fn default_segments() -> Vec<Vec<String>> {
         vec![
             vec![
@@ -126,24 +360,266 @@ impl Config {
 }
 impl Default for Config {
     fn default() -> Self {
-        serde_yaml::from_str(DEFAULT_CONFIG_STR).unwrap()
+        Self::load_from_str(DEFAULT_CONFIG_STR).unwrap()
     }
 }
 
 impl Config {
+    // The per-segment timeout_ms takes precedence over the global one, 0 means no timeout
+    pub fn segment_timeout(&self, name: &str) -> Option<Duration> {
+        let timeout_ms = match name {
+            "os" => self.os.timeout_ms,
+            "shell" => self.shell.timeout_ms,
+            "directory" => self.directory.timeout_ms,
+            "user" => self.user.timeout_ms,
+            "status" => self.status.timeout_ms,
+            "time" => self.time.timeout_ms,
+            "duration" => self.duration.timeout_ms,
+            "git_repo" => self.git_repo.timeout_ms,
+            "git_user" => self.git_user.timeout_ms,
+            "venv" => self.venv.timeout_ms,
+            _ => None,
+        };
+
+        match timeout_ms.unwrap_or(self.timeout_ms) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
     pub fn load_from_str(s: &str) -> Result<Self> {
-        let config = serde_yaml::from_str(s)?;
+        Self::validate_layer(s)?;
+        let value = Self::parse_layer(s)?;
+
+        Self::load_from_value(value)
+    }
+
+    fn parse_layer(s: &str) -> Result<Value> {
+        if s.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+
+        let value = serde_yaml::from_str(s)?;
+        Ok(value)
+    }
+
+    // Deserializes a layer alone to report errors with their locations
+    fn validate_layer(s: &str) -> Result<()> {
+        if !s.trim().is_empty() {
+            serde_yaml::from_str::<Self>(s)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_layer<P: AsRef<Path>>(path: P) -> Result<Layer> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let value =
+            Self::parse_layer(&text).map_err(|err| anyhow!("{}: {}", path.display(), err))?;
+
+        Ok(Layer {
+            path: path.to_path_buf(),
+            text,
+            value,
+        })
+    }
+
+    fn user_layer<P: AsRef<Path>>(config_path: P) -> Result<Layer> {
+        if config_path.as_ref().exists() {
+            Self::read_layer(config_path)
+        } else {
+            // No config file
+            let _ = Self::save_default_config(&config_path); // Ignore error
+
+            Ok(Layer {
+                path: config_path.as_ref().to_path_buf(),
+                text: DEFAULT_CONFIG_STR.to_string(),
+                value: Self::parse_layer(DEFAULT_CONFIG_STR)?,
+            })
+        }
+    }
+
+    fn find_project_config(current_dir: &Path) -> Option<PathBuf> {
+        current_dir
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    // The system, user and project configuration files in the order of precedence
+    pub fn layer_paths<P: AsRef<Path>>(config_path: P, current_dir: &Path) -> Vec<PathBuf> {
+        let config_path = config_path.as_ref();
+        let system_config_path = Path::new(SYSTEM_CONFIG_PATH);
+
+        let mut paths = Vec::new();
+
+        if system_config_path != config_path && system_config_path.is_file() {
+            paths.push(system_config_path.to_path_buf());
+        }
+
+        paths.push(config_path.to_path_buf());
+
+        if let Some(project_config_path) = Self::find_project_config(current_dir) {
+            paths.push(project_config_path);
+        }
+
+        paths
+    }
+
+    pub fn load<P: AsRef<Path>>(config_path: P, current_dir: &Path) -> Result<Self> {
+        let config_path = config_path.as_ref();
+        let paths = Self::layer_paths(config_path, current_dir);
+
+        // Check before loading so that every problem is reported at once
+        let strict_env = std::env::var("ALMEL_STRICT").is_ok_and(|v| v == "1");
+        if strict_env {
+            Self::check_strict(&paths)?;
+        }
+
+        let config = Self::load_layers(config_path, &paths)?;
+
+        if config.strict && !strict_env {
+            Self::check_strict(&paths)?;
+        }
 
         Ok(config)
     }
 
-    pub fn load_from_file(file: &File) -> Result<Self> {
-        let config = serde_yaml::from_reader(file)?;
+    // Names the layer that configures each segment: the last file setting its key,
+    // then the theme, then the built-in defaults
+    pub fn segment_sources<P: AsRef<Path>>(
+        config_path: P,
+        current_dir: &Path,
+        theme_name: &str,
+        names: &[&str],
+    ) -> Vec<String> {
+        let layers: Vec<Layer> = Self::layer_paths(config_path, current_dir)
+            .into_iter()
+            .filter_map(|path| Self::read_layer(path).ok())
+            .collect();
+
+        let theme: Value = theme::find_theme(theme_name)
+            .and_then(|theme| serde_yaml::from_str(theme).ok())
+            .unwrap_or(Value::Null);
+
+        names
+            .iter()
+            .map(|name| {
+                if let Some(layer) = layers
+                    .iter()
+                    .rev()
+                    .find(|layer| layer.value.get(name).is_some())
+                {
+                    layer.path.display().to_string()
+                } else if theme.get(name).is_some() {
+                    format!("theme {}", theme_name)
+                } else {
+                    "default".to_string()
+                }
+            })
+            .collect()
+    }
+
+    fn check_strict(paths: &[PathBuf]) -> Result<()> {
+        let diagnostics = check::check_files(paths);
+
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+
+        Err(anyhow!(
+            "Found {} problem(s) in strict mode:\n{}",
+            diagnostics.len(),
+            diagnostics.join("\n")
+        ))
+    }
+
+    // A broken layer is reported and skipped, so that it does not discard the others
+    fn load_layers(config_path: &Path, paths: &[PathBuf]) -> Result<Self> {
+        let mut layers = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let layer = if path == config_path {
+                Self::user_layer(path)
+            } else {
+                Self::read_layer(path)
+            };
+
+            match layer {
+                Ok(layer) => layers.push(layer),
+                Err(err) => eprintln!("{}", err),
+            }
+        }
+
+        let merge = |layers: &[Layer]| {
+            let mut value = Value::Null;
+            for layer in layers {
+                merge_yaml(&mut value, layer.value.clone());
+            }
+            value
+        };
+
+        if let Ok(config) = Self::load_from_value(merge(&layers)) {
+            return Ok(config);
+        }
+
+        // Deserialize the layers one by one only when the merged configuration is invalid
+        layers.retain(|layer| match Self::validate_layer(&layer.text) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("{}: {}", layer.path.display(), err);
+                false
+            }
+        });
+
+        Self::load_from_value(merge(&layers))
+    }
+
+    fn load_from_value(value: Value) -> Result<Self> {
+        let theme_name = value
+            .get("theme")
+            .and_then(Value::as_str)
+            .unwrap_or(theme::DEFAULT_THEME);
+        let theme = theme::find_theme(theme_name)
+            .ok_or_else(|| anyhow!("Unknown theme: {}", theme_name))?;
+
+        let mut merged = serde_yaml::from_str(theme)?;
+        merge_yaml(&mut merged, value);
+
+        if let Some(status) = merged.get_mut("status") {
+            status::merge_code_styles(status);
+        }
+
+        let mut config: Self = serde_yaml::from_value(merged)?;
+        config.time.strip_legacy_icon();
+        config.apply_icon_set();
 
         Ok(config)
     }
 
-    fn save_default_config<P: AsRef<Path>>(config_path: P) -> Result<()> {
+    fn apply_icon_set(&mut self) {
+        if let Some(icon_set) = std::env::var_os("ALMEL_ICONS") {
+            match icon_set.to_string_lossy().parse() {
+                Ok(icon_set) => self.icon_set = icon_set,
+                Err(err) => eprintln!("{}", err),
+            }
+        }
+
+        let icon_set = self.icon_set;
+
+        self.os.apply_icon_set(icon_set);
+        self.shell.apply_icon_set(icon_set);
+        self.git_repo.apply_icon_set(icon_set);
+        self.git_user.apply_icon_set(icon_set);
+        self.status.apply_icon_set(icon_set);
+        self.time.apply_icon_set(icon_set);
+        self.duration.apply_icon_set(icon_set);
+        self.venv.apply_icon_set(icon_set);
+        self.segment_separators.apply_icon_set(icon_set);
+    }
+
+    pub fn save_default_config<P: AsRef<Path>>(config_path: P) -> Result<()> {
         let config_path = config_path.as_ref();
 
         if let Some(config_dir) = config_path.parent() {
@@ -156,18 +632,39 @@ impl Config {
         Ok(())
     }
 
-    pub fn load_from_file_or_create_default<P: AsRef<Path>>(config_path: P) -> Result<Config> {
-        if let Ok(config_file) = File::open(&config_path) {
-            let config = Self::load_from_file(&config_file)?;
+    pub fn save_theme<P: AsRef<Path>>(config_path: P, theme_name: &str) -> Result<()> {
+        let config_path = config_path.as_ref();
 
-            Ok(config)
-        } else {
-            // No config file
-            let _ = Self::save_default_config(&config_path); // Ignore error
-            let config = Self::load_from_str(DEFAULT_CONFIG_STR)?;
+        if !config_path.exists() {
+            Self::save_default_config(config_path)?;
+        }
 
-            Ok(config)
+        let mut s = String::new();
+        File::open(config_path)?.read_to_string(&mut s)?;
+
+        let theme_line = format!("theme: {}", theme_name);
+        let mut found = false;
+        let mut lines: Vec<&str> = s
+            .lines()
+            .map(|line| {
+                if !found && line.starts_with("theme:") {
+                    found = true;
+                    &theme_line
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        if !found {
+            lines.insert(0, &theme_line);
         }
+
+        let mut config_file = File::create(config_path)?;
+        config_file.write_all(lines.join("\n").as_bytes())?;
+        config_file.write_all(b"\n")?;
+
+        Ok(())
     }
 
     pub fn config_path() -> PathBuf {
@@ -184,9 +681,92 @@ impl Config {
 
             path
         } else {
-            PathBuf::from("/etc/almel/almel.yaml")
+            PathBuf::from(SYSTEM_CONFIG_PATH)
+        }
+    }
+}
+
+// A configuration file parsed once and merged with the others
+struct Layer {
+    path: PathBuf,
+    text: String,
+    value: Value,
+}
+
+// Overwrites the values of `base` with the ones set in `overlay`, merging mappings recursively
+fn merge_yaml(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (_, Value::Null) => {}
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_yaml(base_value, value),
+                    None if value.is_null() => {}
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
         }
+        (base, overlay) => *base = overlay,
     }
 }
 
-const DEFAULT_CONFIG_STR: &str = include_str!("almel.yaml");
+#[test]
+fn test_segment_timeout() {
+    let mut config = Config::default();
+    assert_eq!(config.segment_timeout("git_repo"), None);
+
+    config.timeout_ms = 100;
+    config.git_repo.timeout_ms = Some(500);
+    config.time.timeout_ms = Some(0);
+
+    assert_eq!(
+        config.segment_timeout("git_repo"),
+        Some(Duration::from_millis(500))
+    );
+    assert_eq!(
+        config.segment_timeout("user"),
+        Some(Duration::from_millis(100))
+    );
+    assert_eq!(config.segment_timeout("time"), None);
+}
+
+#[test]
+fn test_merge_yaml() {
+    let mut base: Value =
+        serde_yaml::from_str("a: { b: 1, c: [1, 2] }\nd: 2\ne: { f: 3 }\n").unwrap();
+    let overlay: Value = serde_yaml::from_str("a: { c: [3] }\ne: { g: 4 }\nh: ~\n").unwrap();
+    let expected: Value =
+        serde_yaml::from_str("a: { b: 1, c: [3] }\nd: 2\ne: { f: 3, g: 4 }\n").unwrap();
+
+    merge_yaml(&mut base, overlay);
+    assert_eq!(base, expected);
+}
+
+#[test]
+fn test_time_legacy_format() {
+    let config = Config::load_from_str("time:\n  format: \"\\uf017 %H:%M\"\n").unwrap();
+    assert_eq!(config.time.format, "%H:%M");
+
+    let config =
+        Config::load_from_str("time:\n  icon: \"\"\n  format: \"\\uf017 %H:%M\"\n").unwrap();
+    assert_eq!(config.time.format, "\u{f017} %H:%M");
+}
+
+#[test]
+fn test_status_code_style() {
+    let config = Config::load_from_str(
+        "status:\n  failed:\n    style:\n      background: Red\n      bold: true\n  codes:\n    130:\n      style:\n        foreground: Yellow\n",
+    )
+    .unwrap();
+
+    let style = config.status.codes[&130].style.as_ref().unwrap();
+    assert_eq!(style.foreground, Color::Yellow);
+    assert_eq!(style.background, Color::Red);
+    assert!(style.bold);
+}
+
+pub const DEFAULT_CONFIG_STR: &str = include_str!("almel.yaml");
+const SYSTEM_CONFIG_PATH: &str = "/etc/almel/almel.yaml";
+const PROJECT_CONFIG_FILE_NAME: &str = ".almel.yaml";
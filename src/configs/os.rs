@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::default::Default;
 
-use crate::configs::SegmentStyle;
+use crate::configs::{IconSet, SegmentStyle};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
@@ -13,6 +13,27 @@ pub struct Config {
 
     #[serde(default)]
     pub windows: ConfigWindows,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+impl Config {
+    pub fn apply_icon_set(&mut self, icon_set: IconSet) {
+        let linux = &mut self.linux.icon;
+        icon_set.replace(linux, &ConfigLinux::default_icon(), "Linux", "Linux");
+
+        let mac = &mut self.mac.icon;
+        icon_set.replace(mac, &ConfigMac::default_icon(), "Mac", "Mac");
+
+        let windows = &mut self.windows.icon;
+        icon_set.replace(
+            windows,
+            &ConfigWindows::default_icon(),
+            "Windows",
+            "Windows",
+        );
+    }
 }
 
 // Linux
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::default::Default;
 
-use crate::configs::SegmentStyle;
+use crate::configs::{IconSet, SegmentStyle};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
@@ -13,6 +13,37 @@ pub struct Config {
 
     #[serde(default)]
     pub fish: ConfigFish,
+
+    #[serde(default)]
+    pub powershell: ConfigPowerShell,
+
+    #[serde(default)]
+    pub nu: ConfigNu,
+
+    #[serde(default)]
+    pub elvish: ConfigElvish,
+
+    #[serde(default)]
+    pub xonsh: ConfigXonsh,
+
+    #[serde(default)]
+    pub tcsh: ConfigTcsh,
+
+    #[serde(default)]
+    pub ksh: ConfigKsh,
+
+    #[serde(default)]
+    pub mksh: ConfigMksh,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+impl Config {
+    pub fn apply_icon_set(&mut self, icon_set: IconSet) {
+        let fish = &mut self.fish.icon;
+        icon_set.replace(fish, &ConfigFish::default_icon(), "Fish", "Fish");
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,3 +111,157 @@ impl Default for ConfigFish {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigPowerShell {
+    #[serde(default)]
+    pub style: SegmentStyle,
+
+    #[serde(default = "ConfigPowerShell::default_icon")]
+    pub icon: String,
+}
+impl ConfigPowerShell {
+    fn default_icon() -> String {
+        "PowerShell".to_string()
+    }
+}
+impl Default for ConfigPowerShell {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            icon: Self::default_icon(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigNu {
+    #[serde(default)]
+    pub style: SegmentStyle,
+
+    #[serde(default = "ConfigNu::default_icon")]
+    pub icon: String,
+}
+impl ConfigNu {
+    fn default_icon() -> String {
+        "Nu".to_string()
+    }
+}
+impl Default for ConfigNu {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            icon: Self::default_icon(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigElvish {
+    #[serde(default)]
+    pub style: SegmentStyle,
+
+    #[serde(default = "ConfigElvish::default_icon")]
+    pub icon: String,
+}
+impl ConfigElvish {
+    fn default_icon() -> String {
+        "Elvish".to_string()
+    }
+}
+impl Default for ConfigElvish {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            icon: Self::default_icon(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigXonsh {
+    #[serde(default)]
+    pub style: SegmentStyle,
+
+    #[serde(default = "ConfigXonsh::default_icon")]
+    pub icon: String,
+}
+impl ConfigXonsh {
+    fn default_icon() -> String {
+        "Xonsh".to_string()
+    }
+}
+impl Default for ConfigXonsh {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            icon: Self::default_icon(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigTcsh {
+    #[serde(default)]
+    pub style: SegmentStyle,
+
+    #[serde(default = "ConfigTcsh::default_icon")]
+    pub icon: String,
+}
+impl ConfigTcsh {
+    fn default_icon() -> String {
+        "Tcsh".to_string()
+    }
+}
+impl Default for ConfigTcsh {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            icon: Self::default_icon(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigKsh {
+    #[serde(default)]
+    pub style: SegmentStyle,
+
+    #[serde(default = "ConfigKsh::default_icon")]
+    pub icon: String,
+}
+impl ConfigKsh {
+    fn default_icon() -> String {
+        "Ksh".to_string()
+    }
+}
+impl Default for ConfigKsh {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            icon: Self::default_icon(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigMksh {
+    #[serde(default)]
+    pub style: SegmentStyle,
+
+    #[serde(default = "ConfigMksh::default_icon")]
+    pub icon: String,
+}
+impl ConfigMksh {
+    fn default_icon() -> String {
+        "Mksh".to_string()
+    }
+}
+impl Default for ConfigMksh {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            icon: Self::default_icon(),
+        }
+    }
+}
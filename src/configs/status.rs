@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
 use std::default::Default;
 
-use crate::configs::SegmentStyle;
+use crate::configs::{merge_yaml, IconSet, SegmentStyle};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
@@ -13,6 +15,28 @@ pub struct Config {
 
     #[serde(default)]
     pub failed: ConfigFailed,
+
+    #[serde(default)]
+    pub codes: BTreeMap<i32, ConfigCode>,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+impl Config {
+    pub fn apply_icon_set(&mut self, icon_set: IconSet) {
+        let icons = &mut self.icons;
+
+        icon_set.replace(
+            &mut icons.succeeded,
+            &ConfigIcons::default_succeeded(),
+            "✓",
+            "ok",
+        );
+        icon_set.replace(&mut icons.failed, &ConfigIcons::default_failed(), "✗", "x");
+        icon_set.replace(&mut icons.root, &ConfigIcons::default_root(), "⚡", "#");
+        icon_set.replace(&mut icons.jobs, &ConfigIcons::default_jobs(), "⚙", "&");
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -67,17 +91,58 @@ pub struct ConfigFailed {
 
     #[serde(default = "ConfigFailed::default_display_exit_status")]
     pub display_exit_status: bool,
+
+    #[serde(default = "ConfigFailed::default_display_signal_name")]
+    pub display_signal_name: bool,
+
+    #[serde(default)]
+    pub display_meaning: bool,
 }
 impl ConfigFailed {
     fn default_display_exit_status() -> bool {
         true
     }
+    fn default_display_signal_name() -> bool {
+        true
+    }
 }
 impl Default for ConfigFailed {
     fn default() -> Self {
         Self {
             style: Default::default(),
             display_exit_status: Self::default_display_exit_status(),
+            display_signal_name: Self::default_display_signal_name(),
+            display_meaning: false,
+        }
+    }
+}
+
+// Overrides for a specific exit status
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigCode {
+    #[serde(default)]
+    pub icon: Option<String>,
+
+    #[serde(default)]
+    pub style: Option<SegmentStyle>,
+}
+
+// A style under codes only overrides the fields it sets over failed.style, so the
+// YAML of the status config is merged before it is deserialized
+pub fn merge_code_styles(status: &mut Value) {
+    let failed_style = status
+        .get("failed")
+        .and_then(|failed| failed.get("style"))
+        .cloned()
+        .unwrap_or(Value::Null);
+
+    if let Some(Value::Mapping(codes)) = status.get_mut("codes") {
+        for (_, code) in codes.iter_mut() {
+            if let Some(style) = code.get_mut("style") {
+                let mut merged = failed_style.clone();
+                merge_yaml(&mut merged, std::mem::replace(style, Value::Null));
+                *style = merged;
+            }
         }
     }
 }
@@ -0,0 +1,19 @@
+pub const DEFAULT_THEME: &str = "agnoster";
+
+pub static THEMES: &[(&str, &str)] = &[
+    ("agnoster", include_str!("themes/agnoster.yaml")),
+    ("pastel", include_str!("themes/pastel.yaml")),
+    ("monochrome", include_str!("themes/monochrome.yaml")),
+    ("high-contrast", include_str!("themes/high-contrast.yaml")),
+    (
+        "light-background",
+        include_str!("themes/light-background.yaml"),
+    ),
+];
+
+pub fn find_theme(name: &str) -> Option<&'static str> {
+    THEMES
+        .iter()
+        .find(|(theme_name, _)| *theme_name == name)
+        .map(|(_, theme)| *theme)
+}
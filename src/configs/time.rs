@@ -1,34 +1,60 @@
 use serde::{Deserialize, Serialize};
 use std::default::Default;
 
-use crate::configs::SegmentStyle;
+use crate::configs::{IconSet, SegmentStyle};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub style: SegmentStyle,
 
+    #[serde(default = "Config::default_icon")]
+    pub icon: String,
+
     #[serde(default = "Config::default_format")]
     pub format: String,
 
     #[serde(default = "Config::default_utc")]
     pub utc: bool,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 impl Config {
+    fn default_icon() -> String {
+        "\u{f017}".to_string() // nf-fa-clock_o
+    }
     fn default_format() -> String {
-        // nf-fa-clock_o
-        "\u{f017} %Y/%m/%d %H:%M:%S".to_string()
+        "%Y/%m/%d %H:%M:%S".to_string()
     }
     fn default_utc() -> bool {
         false
     }
+
+    // Configuration files created before `icon` existed put the clock in `format`, which
+    // would show it twice
+    pub fn strip_legacy_icon(&mut self) {
+        if self.icon.is_empty() {
+            return;
+        }
+
+        if let Some(format) = self.format.strip_prefix(&Self::default_icon()) {
+            self.format = format.trim_start().to_string();
+        }
+    }
+
+    pub fn apply_icon_set(&mut self, icon_set: IconSet) {
+        icon_set.replace(&mut self.icon, &Self::default_icon(), "◷", "");
+    }
 }
 impl Default for Config {
     fn default() -> Self {
         Self {
             style: Default::default(),
+            icon: Self::default_icon(),
             format: Self::default_format(),
             utc: Self::default_utc(),
+            timeout_ms: None,
         }
     }
 }
@@ -10,6 +10,9 @@ pub struct Config {
 
     #[serde(default = "Config::default_display_host")]
     pub display_host: bool,
+
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 impl Config {
@@ -22,6 +25,7 @@ impl Default for Config {
         Self {
             style: Default::default(),
             display_host: Self::default_display_host(),
+            timeout_ms: None,
         }
     }
 }
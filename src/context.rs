@@ -1,22 +1,41 @@
 use git2::Repository;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::color::ColorDepth;
 use crate::configs::Config;
 use crate::opt::PromptArgs;
 
-pub struct Context<'ctx> {
+pub fn current_dir() -> PathBuf {
+    std::env::var_os("PWD")
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+pub fn load_config() -> Config {
+    Config::load(Config::config_path(), &current_dir())
+        .map_err(|err| {
+            eprintln!("{}", err);
+        })
+        .unwrap_or_default()
+}
+
+// Shared by the threads building the segments, so it holds the location of the
+// repository rather than a git2::Repository, which is not Sync. The configuration and
+// the arguments are owned, since segments that time out keep running in the background.
+pub struct Context {
     pub current_dir: PathBuf,
-    pub config: &'ctx Config,
-    pub opt: &'ctx PromptArgs,
-    pub git_repo: Option<Repository>,
+    pub config: Arc<Config>,
+    pub opt: Arc<PromptArgs>,
+    pub git_dir: Option<PathBuf>,
+    pub git_workdir: Option<PathBuf>,
+    pub color_depth: ColorDepth,
 }
 
-impl<'ctx> Context<'ctx> {
-    pub fn new(opt: &'ctx PromptArgs, config: &'ctx Config) -> Self {
-        let current_dir = std::env::var_os("PWD")
-            .map(PathBuf::from)
-            .or_else(|| std::env::current_dir().ok())
-            .unwrap_or_default();
+impl Context {
+    pub fn new(opt: Arc<PromptArgs>, config: Arc<Config>) -> Self {
+        let current_dir = current_dir();
 
         let git_repo = if opt.no_git {
             None
@@ -24,11 +43,26 @@ impl<'ctx> Context<'ctx> {
             Repository::discover(&current_dir).ok()
         };
 
+        let git_dir = git_repo.as_ref().map(|repo| repo.path().to_path_buf());
+        let git_workdir = git_repo
+            .as_ref()
+            .and_then(|repo| repo.workdir())
+            .map(Path::to_path_buf);
+
+        let color_depth = ColorDepth::from_mode(opt.color);
+
         Self {
             current_dir,
             config,
             opt,
-            git_repo,
+            git_dir,
+            git_workdir,
+            color_depth,
         }
     }
+
+    // Opens a handle to the repository for the calling thread
+    pub fn open_git_repo(&self) -> Option<Repository> {
+        Repository::open(self.git_dir.as_ref()?).ok()
+    }
 }
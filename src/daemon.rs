@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::context::Context;
+use crate::segments::RepoStatus;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    git_dir: PathBuf,
+    display_tag: bool,
+}
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use anyhow::{anyhow, Result};
+
+    use super::RepoStatus;
+    use crate::context::Context;
+
+    pub fn query_repo_status(_context: &Context) -> Option<RepoStatus> {
+        None
+    }
+
+    pub fn serve() -> Result<()> {
+        Err(anyhow!("almel daemon is not supported on Windows"))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod imp {
+    use anyhow::{anyhow, Result};
+    use git2::Repository;
+    use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{RepoStatus, Request};
+    use crate::context::Context;
+    use crate::segments::repo_status;
+
+    // Short enough for the prompt, which computes the status itself when the daemon is slow
+    const CLIENT_TIMEOUT: Duration = Duration::from_millis(100);
+
+    fn socket_path() -> PathBuf {
+        match std::env::var_os("XDG_RUNTIME_DIR") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("almel.sock"),
+            _ => std::env::temp_dir().join(format!("almel-{}.sock", users::get_current_uid())),
+        }
+    }
+
+    // The fallback path is in the shared temporary directory, where another user could
+    // create the socket first and answer with a branch name that the shell expands
+    fn is_owned_by_current_user(path: &Path) -> bool {
+        std::fs::symlink_metadata(path)
+            .is_ok_and(|metadata| metadata.uid() == users::get_current_uid())
+    }
+
+    pub fn query_repo_status(context: &Context) -> Option<RepoStatus> {
+        let git_dir = context.git_dir.as_ref()?;
+
+        let path = socket_path();
+        if !is_owned_by_current_user(&path) {
+            return None;
+        }
+
+        let mut stream = UnixStream::connect(path).ok()?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+
+        let request = Request {
+            git_dir: git_dir.clone(),
+            display_tag: context.config.git_repo.display_tag,
+        };
+
+        let mut line = serde_json::to_string(&request).ok()?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).ok()?;
+
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).ok()?;
+
+        serde_json::from_str(&response).ok()?
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum WatchState {
+        Pending,
+        Watched,
+        Failed,
+    }
+
+    // A repository being watched, with its statuses serialized for each value of display_tag
+    struct Entry {
+        git_dir: PathBuf,
+        workdir: Option<PathBuf>,
+        state: WatchState,
+        generation: u64,
+        responses: HashMap<bool, String>,
+    }
+
+    impl Entry {
+        fn is_affected_by(&self, path: &Path) -> bool {
+            path.starts_with(&self.git_dir)
+                || self
+                    .workdir
+                    .as_ref()
+                    .is_some_and(|workdir| path.starts_with(workdir))
+        }
+    }
+
+    #[test]
+    fn test_entry_is_affected_by() {
+        let entry = Entry {
+            git_dir: PathBuf::from("/repo/.git"),
+            workdir: Some(PathBuf::from("/repo")),
+            state: WatchState::Watched,
+            generation: 0,
+            responses: HashMap::new(),
+        };
+
+        assert!(entry.is_affected_by(Path::new("/repo/src/main.rs")));
+        assert!(entry.is_affected_by(Path::new("/repo/.git/index")));
+        assert!(!entry.is_affected_by(Path::new("/repository/file")));
+        assert!(!entry.is_affected_by(Path::new("/other/.git/index")));
+    }
+
+    type Entries = Arc<Mutex<HashMap<PathBuf, Entry>>>;
+
+    fn invalidate(entries: &Entries, event: Event) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+
+        let mut entries = entries.lock().unwrap();
+
+        for entry in entries.values_mut() {
+            if event.paths.iter().any(|path| entry.is_affected_by(path)) {
+                entry.generation += 1;
+                entry.responses.clear();
+            }
+        }
+    }
+
+    fn watch(watcher: &Mutex<RecommendedWatcher>, repo: &Repository) -> bool {
+        let mut watcher = watcher.lock().unwrap();
+
+        let paths = match repo.workdir() {
+            Some(workdir) if repo.path().starts_with(workdir) => vec![workdir],
+            Some(workdir) => vec![workdir, repo.path()],
+            None => vec![repo.path()],
+        };
+
+        paths.into_iter().all(|path| {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .map_err(|err| eprintln!("Failed to watch {}: {}", path.display(), err))
+                .is_ok()
+        })
+    }
+
+    // Watching a large repository recursively takes a while, so it is done in the background
+    fn start_watching(entries: &Entries, watcher: &Arc<Mutex<RecommendedWatcher>>, git_dir: &Path) {
+        let repo = match Repository::open(git_dir) {
+            Ok(repo) => repo,
+            Err(_) => return,
+        };
+
+        entries.lock().unwrap().insert(
+            git_dir.to_path_buf(),
+            Entry {
+                git_dir: repo.path().to_path_buf(),
+                workdir: repo.workdir().map(Path::to_path_buf),
+                state: WatchState::Pending,
+                generation: 0,
+                responses: HashMap::new(),
+            },
+        );
+
+        let entries = Arc::clone(entries);
+        let watcher = Arc::clone(watcher);
+        let git_dir = git_dir.to_path_buf();
+
+        thread::spawn(move || {
+            let state = if watch(&watcher, &repo) {
+                WatchState::Watched
+            } else {
+                WatchState::Failed
+            };
+
+            if let Some(entry) = entries.lock().unwrap().get_mut(&git_dir) {
+                entry.state = state;
+            }
+        });
+    }
+
+    fn respond(
+        entries: &Entries,
+        watcher: &Arc<Mutex<RecommendedWatcher>>,
+        request: &Request,
+    ) -> String {
+        let cached = entries.lock().unwrap().get(&request.git_dir).map(|entry| {
+            let response = entry.responses.get(&request.display_tag).cloned();
+            (response, entry.state, entry.generation)
+        });
+
+        // "null" means unknown, in which case the client computes the status itself
+        let generation = match cached {
+            Some((Some(response), _, _)) => return response,
+            Some((None, WatchState::Pending, _)) => return "null".to_string(),
+            Some((None, _, generation)) => generation,
+            None => {
+                start_watching(entries, watcher, &request.git_dir);
+                return "null".to_string();
+            }
+        };
+
+        let repo = match Repository::open(&request.git_dir) {
+            Ok(repo) => repo,
+            Err(_) => return "null".to_string(),
+        };
+
+        let status = repo_status(&repo, request.display_tag);
+        let response = serde_json::to_string(&Some(status)).unwrap_or_else(|_| "null".to_string());
+
+        // Do not cache a status that may have changed while it was computed
+        if let Some(entry) = entries.lock().unwrap().get_mut(&request.git_dir) {
+            if entry.state == WatchState::Watched && entry.generation == generation {
+                entry
+                    .responses
+                    .insert(request.display_tag, response.clone());
+            }
+        }
+
+        response
+    }
+
+    fn handle(
+        stream: UnixStream,
+        entries: &Entries,
+        watcher: &Arc<Mutex<RecommendedWatcher>>,
+    ) -> Result<()> {
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+
+        let request: Request = serde_json::from_str(&line)?;
+        let mut response = respond(entries, watcher, &request);
+        response.push('\n');
+
+        (&stream).write_all(response.as_bytes())?;
+        Ok(())
+    }
+
+    fn bind(path: &Path) -> Result<UnixListener> {
+        if path.symlink_metadata().is_ok() {
+            if !is_owned_by_current_user(path) {
+                return Err(anyhow!("{} is owned by another user", path.display()));
+            }
+
+            if UnixStream::connect(path).is_ok() {
+                return Err(anyhow!(
+                    "almel daemon is already running: {}",
+                    path.display()
+                ));
+            }
+
+            // Left by a daemon that did not exit cleanly
+            std::fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+        Ok(listener)
+    }
+
+    pub fn serve() -> Result<()> {
+        let path = socket_path();
+        let listener = bind(&path)?;
+
+        let entries: Entries = Default::default();
+
+        let watcher = {
+            let entries = Arc::clone(&entries);
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) => invalidate(&entries, event),
+                Err(err) => eprintln!("{}", err),
+            })?
+        };
+        let watcher = Arc::new(Mutex::new(watcher));
+
+        eprintln!("Listening on {}", path.display());
+
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    continue;
+                }
+            };
+
+            let entries = Arc::clone(&entries);
+            let watcher = Arc::clone(&watcher);
+
+            thread::spawn(move || {
+                if let Err(err) = handle(stream, &entries, &watcher) {
+                    eprintln!("{}", err);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// Falls back to None when the daemon is not running
+pub fn query_repo_status(context: &Context) -> Option<RepoStatus> {
+    imp::query_repo_status(context)
+}
+
+pub fn run() {
+    if let Err(err) = imp::serve() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::configs::color::format_color;
+use crate::configs::{Config, SegmentStyle};
+use crate::context::{self, Context};
+use crate::opt::PromptArgs;
+use crate::segments;
+use crate::table::{ignore_broken_pipe, print_table};
+
+struct Row {
+    line: String,
+    name: String,
+    source: String,
+    rendered: &'static str,
+    time: Duration,
+    style: String,
+    content: String,
+}
+
+fn describe_style(style: &SegmentStyle) -> String {
+    let mut description = format!(
+        "{} on {}",
+        format_color(&style.foreground),
+        format_color(&style.background)
+    );
+
+    let attributes = [
+        (style.bold, "bold"),
+        (style.dimmed, "dimmed"),
+        (style.italic, "italic"),
+        (style.underline, "underline"),
+        (style.blink, "blink"),
+        (style.reverse, "reverse"),
+        (style.strikethrough, "strikethrough"),
+    ];
+
+    for (enabled, attribute) in attributes {
+        if enabled {
+            description.push(' ');
+            description.push_str(attribute);
+        }
+    }
+
+    description
+}
+
+#[test]
+fn test_describe_style() {
+    use ansi_term::Color;
+
+    let style = SegmentStyle {
+        foreground: Color::White,
+        background: Color::RGB(0x12, 0x34, 0x56),
+        bold: true,
+        italic: true,
+        ..SegmentStyle::default()
+    };
+
+    assert_eq!(describe_style(&style), "White on #123456 bold italic");
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn explain(context: &Arc<Context>, lines: &[(String, &[String])]) -> Vec<Row> {
+    let names: Vec<(&str, &str)> = lines
+        .iter()
+        .flat_map(|(line, names)| names.iter().map(move |name| (line.as_str(), name.as_str())))
+        .collect();
+
+    let segment_names: Vec<&str> = names.iter().map(|(_, name)| *name).collect();
+    let results = segments::build_segments(context, &segment_names);
+    let sources = Config::segment_sources(
+        Config::config_path(),
+        &context.current_dir,
+        &context.config.theme,
+        &segment_names,
+    );
+
+    let mut rows = Vec::with_capacity(results.len());
+
+    for (((line, name), built), source) in names.into_iter().zip(results).zip(sources) {
+        let time = built.elapsed;
+
+        let (rendered, style, content) = match built.result {
+            Ok(Some(segment)) => {
+                let content = match segment.icon {
+                    Some(icon) if segment.content.is_empty() => icon.to_string(),
+                    Some(icon) => format!("{} {}", icon, segment.content),
+                    None => segment.content,
+                };
+
+                let rendered = if built.timed_out { "timeout" } else { "yes" };
+
+                (rendered, describe_style(&segment.style), content)
+            }
+            Ok(None) => ("no", String::new(), String::new()),
+            Err(error) => ("error", String::new(), error.to_string()),
+        };
+
+        rows.push(Row {
+            line: line.to_string(),
+            name: name.to_string(),
+            source,
+            rendered,
+            time,
+            style,
+            content,
+        });
+    }
+
+    rows
+}
+
+fn print_rows(rows: &[Row]) -> io::Result<()> {
+    let header = [
+        "LINE", "SEGMENT", "SOURCE", "RENDERED", "TIME", "STYLE", "CONTENT",
+    ];
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            vec![
+                row.line.clone(),
+                row.name.clone(),
+                row.source.clone(),
+                row.rendered.to_string(),
+                format_duration(row.time),
+                row.style.clone(),
+                row.content.clone(),
+            ]
+        })
+        .collect();
+
+    print_table(&header, &cells)
+}
+
+fn print_report(context_time: Duration, segments_time: Duration, rows: &[Row]) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    writeln!(
+        out,
+        "Context (git repository discovery): {}",
+        format_duration(context_time)
+    )?;
+    writeln!(out)?;
+
+    print_rows(rows)?;
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Total: {}",
+        format_duration(context_time + segments_time)
+    )
+}
+
+pub fn run(args: PromptArgs) {
+    let config = Arc::new(context::load_config());
+
+    // Explain only the prompt selected by the mode flags, as `almel prompt` prints it
+    let mut lines: Vec<(String, &[String])> = if args.right {
+        vec![("right".to_string(), &config.right_segments)]
+    } else if args.transient {
+        vec![("transient".to_string(), &config.transient_segments)]
+    } else if args.continuation {
+        vec![("continuation".to_string(), &config.continuation_segments)]
+    } else {
+        config
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(i, line)| ((i + 1).to_string(), line.as_slice()))
+            .collect()
+    };
+    lines.retain(|(_, names)| !names.is_empty());
+
+    let start = Instant::now();
+    let context = Arc::new(Context::new(Arc::new(args), Arc::clone(&config)));
+    let context_time = start.elapsed();
+
+    // Segments are built concurrently, so the total is less than the sum of the times
+    let start = Instant::now();
+    let rows = explain(&context, &lines);
+    let segments_time = start.elapsed();
+
+    if let Err(err) = ignore_broken_pipe(print_report(context_time, segments_time, &rows)) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,123 @@
+# Private to this user, since the prompt written there is expanded by the shell
+ALMEL_ASYNC_DIR="$(mktemp -d "${TMPDIR:-/tmp}/almel.XXXXXXXX")"
+ALMEL_ASYNC_ID=0
+
+almel_trap_command() {
+    # Command of the trap on the signal $2, unquoted from its trap -p output in $1.
+    # The caller runs trap -p at the top level, since functions do not see the DEBUG trap.
+    local trap_line="$1"
+    trap_line="${trap_line#trap -- }"
+    trap_line="${trap_line% $2}"
+    eval "ALMEL_TRAP_COMMAND=$trap_line"
+}
+
+almel_async_start() {
+    [[ -d "$ALMEL_ASYNC_DIR" ]] || return
+
+    local file="$ALMEL_ASYNC_DIR/$ALMEL_ASYNC_ID"
+
+    rm -f "$ALMEL_ASYNC_DIR"/*
+
+    # Double fork to keep the job out of the job table
+    ( (
+        almel prompt bash "$@" > "$file.tmp" &&
+            mv "$file.tmp" "$file" &&
+            kill -USR1 $$
+    ) &>/dev/null & )
+}
+
+almel_async_redraw() {
+    # Only the result of the job started for the current prompt, not of an earlier one
+    local file="$ALMEL_ASYNC_DIR/$ALMEL_ASYNC_ID"
+    [[ -f "$file" ]] || return
+
+    local prompt
+    prompt="$(< "$file")"
+    rm -f "$file"
+
+    local old_lines="${PS1//[^$'\n']/}"
+    local new_lines="${prompt//[^$'\n']/}"
+    PS1="$prompt"
+
+    # Readline owns the line being edited, so only the lines above it can be redrawn
+    if (( ${#old_lines} > 0 && ${#old_lines} == ${#new_lines} )); then
+        local expanded="${PS1@P}"
+        local upper="${expanded%$'\n'*}"
+        printf '\e7\e[%dA\r%s\e[K\e8' "${#old_lines}" "${upper//$'\n'/$'\e[K\n'}"
+    fi
+}
+
+almel_async_cleanup() {
+    [[ -n "$ALMEL_ASYNC_DIR" ]] && rm -rf "$ALMEL_ASYNC_DIR"
+}
+
+almel_now() {
+    # Microseconds since the epoch (whole seconds before bash 5.0)
+    ALMEL_NOW="${EPOCHREALTIME/[.,]/}"
+    if [[ -z "$ALMEL_NOW" ]]; then
+        ALMEL_NOW=$((SECONDS * 1000000))
+    fi
+}
+
+almel_num_jobs() {
+    # Prompt expansion of \j counts jobs without a subshell (bash 4.4 and later)
+    if ((BASH_VERSINFO[0] > 4 || (BASH_VERSINFO[0] == 4 && BASH_VERSINFO[1] >= 4))); then
+        ALMEL_NUM_JOBS='\j'
+        ALMEL_NUM_JOBS="${ALMEL_NUM_JOBS@P}"
+    else
+        ALMEL_NUM_JOBS="$(jobs -p | wc -l)"
+    fi
+}
+
+almel_preexec() {
+    # Only the first command of a command line, not PROMPT_COMMAND
+    if [[ -z "$ALMEL_PREEXEC_READY" || "$BASH_COMMAND" == almel_precmd* ]]; then
+        return
+    fi
+    unset ALMEL_PREEXEC_READY
+    almel_now
+    ALMEL_START="$ALMEL_NOW"
+}
+
+almel_precmd() {
+    # Both must be read before any other command overwrites them
+    local exit_status="$?" pipestatus=("${PIPESTATUS[@]}")
+    # A job still running for the previous prompt must not redraw this one
+    ALMEL_ASYNC_ID=$((ALMEL_ASYNC_ID + 1))
+    local joined
+    printf -v joined '%s,' "${pipestatus[@]}"
+    almel_now
+    local elapsed=$((ALMEL_NOW - ${ALMEL_START:-$ALMEL_NOW}))
+    local duration
+    printf -v duration '%d.%06d' $((elapsed / 1000000)) $((elapsed % 1000000))
+    unset ALMEL_START
+    almel_num_jobs
+    local args=(-s"$exit_status" --pipestatus="${joined%,}" -j"$ALMEL_NUM_JOBS" -d"$duration")
+
+    # Fast first pass without git, partially redrawn when the full prompt is ready
+    PS1="$(almel prompt bash "${args[@]}" --no-git)"
+    local continuation="$(almel prompt bash "${args[@]}" --no-git --continuation)"
+    if [[ -n "$continuation" ]]; then
+        PS2="$continuation"
+    fi
+    almel_async_start "${args[@]}"
+    ALMEL_PREEXEC_READY=1
+}
+
+trap almel_async_redraw USR1
+
+# Run after the EXIT trap of the user instead of replacing it
+almel_trap_command "$(trap -p EXIT)" EXIT
+if [[ "$ALMEL_TRAP_COMMAND" != *almel_async_cleanup* ]]; then
+    printf -v ALMEL_TRAP_COMMAND '%s\n%s' "$ALMEL_TRAP_COMMAND" almel_async_cleanup
+    trap -- "$ALMEL_TRAP_COMMAND" EXIT
+fi
+
+# Run after the DEBUG trap of the user (e.g. bash-preexec) instead of replacing it
+almel_trap_command "$(trap -p DEBUG)" DEBUG
+if [[ "$ALMEL_TRAP_COMMAND" != *almel_preexec* ]]; then
+    printf -v ALMEL_TRAP_COMMAND '%s\n%s' "$ALMEL_TRAP_COMMAND" almel_preexec
+    trap -- "$ALMEL_TRAP_COMMAND" DEBUG
+fi
+
+PROMPT_COMMAND=almel_precmd
@@ -0,0 +1,57 @@
+function almel_async_render --on-event fish_prompt
+    # Both must be read before any other command overwrites them
+    set -l statuses $status $pipestatus
+    set -l exit_status $statuses[1]
+    set -l pipestatus_arg (string join , $statuses[2..-1])
+    set -l num_jobs (count (jobs -p))
+    set -l duration (math $CMD_DURATION / 1000)
+    set -l args -s$exit_status --pipestatus=$pipestatus_arg -j$num_jobs -d$duration
+
+    # Fast first pass without git, replaced by the full prompt when it is ready
+    set -g almel_prompt (almel prompt fish $args --no-git | string collect)
+    set -g almel_right_prompt (almel prompt fish $args --no-git --right | string collect)
+    set -g almel_transient_prompt (almel prompt fish $args --no-git --transient | string collect)
+
+    if set -q almel_async_pid
+        command kill $almel_async_pid 2>/dev/null
+    end
+
+    fish --private --command "
+        set -U almel_async_right_prompt_$fish_pid (almel prompt fish $args --right | string collect)
+        set -U almel_async_prompt_$fish_pid (almel prompt fish $args | string collect)
+    " &
+    set -g almel_async_pid $last_pid
+    disown $almel_async_pid 2>/dev/null
+end
+
+function almel_async_repaint --on-variable almel_async_prompt_$fish_pid
+    set -l prompt almel_async_prompt_$fish_pid
+    set -l right_prompt almel_async_right_prompt_$fish_pid
+
+    set -q $prompt; or return
+
+    set -g almel_prompt $$prompt
+    set -g almel_right_prompt $$right_prompt
+    set -e almel_async_pid
+    commandline -f repaint
+end
+
+function almel_async_cleanup --on-event fish_exit
+    set -e -U almel_async_prompt_$fish_pid
+    set -e -U almel_async_right_prompt_$fish_pid
+end
+
+function fish_prompt
+    if set -q ALMEL_TRANSIENT; and test -n "$almel_transient_prompt"
+        printf '%s' $almel_transient_prompt
+        return
+    end
+
+    printf '%s' $almel_prompt
+end
+
+function fish_right_prompt
+    set -q ALMEL_TRANSIENT; and return
+
+    printf '%s' $almel_right_prompt
+end
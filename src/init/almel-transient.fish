@@ -0,0 +1,31 @@
+function almel_transient_execute
+    set -l cmd (commandline | string collect)
+    if test -n "$cmd"; and commandline --is-valid
+        set -g ALMEL_TRANSIENT 1
+        commandline -f repaint
+    end
+
+    # Run what Enter was bound to before
+    for command in $argv
+        if contains -- $command (bind --function-names)
+            commandline -f $command
+        else
+            eval $command
+        end
+    end
+end
+
+function almel_transient_reset --on-event fish_preexec
+    set -e ALMEL_TRANSIENT
+end
+
+for mode in default insert
+    # The commands of the existing binding, printed escaped after the key
+    set -l previous (bind -M $mode \r 2>/dev/null | string replace -r '^bind( --preset)?( -M \S+)?( -m \S+)? \S+ ' '')
+    string match -q 'almel_transient_execute*' -- "$previous"; and continue
+
+    if test -z "$previous"
+        set previous execute
+    end
+    bind -M $mode \r "almel_transient_execute $previous"
+end
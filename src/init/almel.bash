@@ -1,7 +1,65 @@
+almel_trap_command() {
+    # Command of the trap on the signal $2, unquoted from its trap -p output in $1.
+    # The caller runs trap -p at the top level, since functions do not see the DEBUG trap.
+    local trap_line="$1"
+    trap_line="${trap_line#trap -- }"
+    trap_line="${trap_line% $2}"
+    eval "ALMEL_TRAP_COMMAND=$trap_line"
+}
+
+almel_now() {
+    # Microseconds since the epoch (whole seconds before bash 5.0)
+    ALMEL_NOW="${EPOCHREALTIME/[.,]/}"
+    if [[ -z "$ALMEL_NOW" ]]; then
+        ALMEL_NOW=$((SECONDS * 1000000))
+    fi
+}
+
+almel_num_jobs() {
+    # Prompt expansion of \j counts jobs without a subshell (bash 4.4 and later)
+    if ((BASH_VERSINFO[0] > 4 || (BASH_VERSINFO[0] == 4 && BASH_VERSINFO[1] >= 4))); then
+        ALMEL_NUM_JOBS='\j'
+        ALMEL_NUM_JOBS="${ALMEL_NUM_JOBS@P}"
+    else
+        ALMEL_NUM_JOBS="$(jobs -p | wc -l)"
+    fi
+}
+
+almel_preexec() {
+    # Only the first command of a command line, not PROMPT_COMMAND
+    if [[ -z "$ALMEL_PREEXEC_READY" || "$BASH_COMMAND" == almel_precmd* ]]; then
+        return
+    fi
+    unset ALMEL_PREEXEC_READY
+    almel_now
+    ALMEL_START="$ALMEL_NOW"
+}
+
 almel_precmd() {
-    STATUS="$?"
-    NUM_JOBS="$(jobs | wc -l)"
-    PS1="$(almel prompt bash -s$STATUS -j$NUM_JOBS -d0)"
+    # Both must be read before any other command overwrites them
+    local exit_status="$?" pipestatus=("${PIPESTATUS[@]}")
+    local joined
+    printf -v joined '%s,' "${pipestatus[@]}"
+    almel_now
+    local elapsed=$((ALMEL_NOW - ${ALMEL_START:-$ALMEL_NOW}))
+    local duration
+    printf -v duration '%d.%06d' $((elapsed / 1000000)) $((elapsed % 1000000))
+    unset ALMEL_START
+    almel_num_jobs
+    local args=(-s"$exit_status" --pipestatus="${joined%,}" -j"$ALMEL_NUM_JOBS" -d"$duration")
+    PS1="$(almel prompt bash "${args[@]}")"
+    local continuation="$(almel prompt bash "${args[@]}" --continuation)"
+    if [[ -n "$continuation" ]]; then
+        PS2="$continuation"
+    fi
+    ALMEL_PREEXEC_READY=1
 }
 
+# Run after the DEBUG trap of the user (e.g. bash-preexec) instead of replacing it
+almel_trap_command "$(trap -p DEBUG)" DEBUG
+if [[ "$ALMEL_TRAP_COMMAND" != *almel_preexec* ]]; then
+    printf -v ALMEL_TRAP_COMMAND '%s\n%s' "$ALMEL_TRAP_COMMAND" almel_preexec
+    trap -- "$ALMEL_TRAP_COMMAND" DEBUG
+fi
+
 PROMPT_COMMAND=almel_precmd
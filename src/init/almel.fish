@@ -1,3 +1,31 @@
 function fish_prompt
-    almel prompt fish -s$status -j(count (jobs -p)) -d(math $CMD_DURATION / 1000)
+    # Both must be read before any other command overwrites them
+    set -l statuses $status $pipestatus
+    set -l exit_status $statuses[1]
+    set -l pipestatus_arg (string join , $statuses[2..-1])
+    set -l num_jobs (count (jobs -p))
+    set -l duration (math $CMD_DURATION / 1000)
+
+    if set -q ALMEL_TRANSIENT
+        set -l transient (almel prompt fish -s$exit_status --pipestatus=$pipestatus_arg -j$num_jobs -d$duration --transient | string collect)
+        if test -n "$transient"
+            printf '%s' $transient
+            return
+        end
+    end
+
+    almel prompt fish -s$exit_status --pipestatus=$pipestatus_arg -j$num_jobs -d$duration
+end
+
+function fish_right_prompt
+    # Both must be read before any other command overwrites them
+    set -l statuses $status $pipestatus
+    set -l exit_status $statuses[1]
+    set -l pipestatus_arg (string join , $statuses[2..-1])
+    set -q ALMEL_TRANSIENT; and return
+
+    set -l num_jobs (count (jobs -p))
+    set -l duration (math $CMD_DURATION / 1000)
+
+    almel prompt fish -s$exit_status --pipestatus=$pipestatus_arg -j$num_jobs -d$duration --right
 end
@@ -0,0 +1,31 @@
+function global:prompt {
+    # Both must be read before any other command overwrites them
+    $success = $global:?
+    $lastExitCode = $global:LASTEXITCODE
+
+    $exitStatus = if ($success) { 0 } elseif ($lastExitCode) { $lastExitCode } else { 1 }
+    $jobs = @(Get-Job | Where-Object { $_.State -eq 'Running' }).Count
+
+    # The last history entry is kept until the next command, so it is only measured once
+    $duration = 0
+    $history = Get-History -Count 1
+    if ($history -and $history.Id -ne $global:AlmelHistoryId) {
+        $global:AlmelHistoryId = $history.Id
+        $duration = ($history.EndExecutionTime - $history.StartExecutionTime).TotalSeconds
+    }
+    $duration = $duration.ToString([System.Globalization.CultureInfo]::InvariantCulture)
+
+    $arguments = @("--exit-status=$exitStatus", "--num-jobs=$jobs", "--duration=$duration")
+
+    $continuation = (& almel prompt powershell @arguments --continuation) -join "`n"
+    if ($continuation -and (Get-Command Set-PSReadLineOption -ErrorAction SilentlyContinue)) {
+        Set-PSReadLineOption -ContinuationPrompt $continuation
+    }
+
+    $prompt = (& almel prompt powershell @arguments) -join "`n"
+
+    # Running almel must not change the exit code seen by the user
+    $global:LASTEXITCODE = $lastExitCode
+
+    $prompt
+}
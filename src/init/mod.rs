@@ -1,5 +1,12 @@
+use crate::context;
 use crate::opt::InitArgs;
 
 pub fn run(args: &InitArgs) {
     print!("{}", args.shell.init_script(args.asynchronous));
+
+    if let Some(script) = args.shell.transient_script() {
+        if !context::load_config().transient_segments.is_empty() {
+            print!("\n{}", script);
+        }
+    }
 }
@@ -1,18 +1,32 @@
+mod bench;
+mod cache;
+mod color;
+mod config;
 mod configs;
 mod context;
+mod daemon;
+mod explain;
 mod init;
 mod opt;
+mod preset;
 mod prompt;
 mod segments;
 mod shell;
+mod table;
 
 use crate::opt::{Opt, Subcommand};
 
 fn main() {
     let opt = Opt::parse();
 
-    match &opt.subcommand {
-        Subcommand::Init(args) => init::run(args),
+    match opt.subcommand {
+        Subcommand::Init(args) => init::run(&args),
         Subcommand::Prompt(args) => prompt::run(args),
+        Subcommand::Preset(args) => preset::run(&args),
+        Subcommand::Config(args) => config::run(&args),
+        Subcommand::Explain(args) => explain::run(args),
+        Subcommand::Bench(args) => bench::run(args),
+        Subcommand::Daemon => daemon::run(),
+        Subcommand::Cache(args) => cache::run(&args),
     };
 }
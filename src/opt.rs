@@ -1,5 +1,9 @@
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::{clap, StructOpt};
 
+use crate::color::{ColorMode, POSSIBLE_COLOR_VALUES};
 use crate::shell::{Shell, POSSIBLE_SHELL_VALUES};
 
 #[derive(Debug, StructOpt)]
@@ -29,6 +33,24 @@ pub enum Subcommand {
 
     #[structopt(about = "Print the prompt")]
     Prompt(PromptArgs),
+
+    #[structopt(about = "Manage built-in themes")]
+    Preset(PresetArgs),
+
+    #[structopt(about = "Manage the configuration file")]
+    Config(ConfigArgs),
+
+    #[structopt(about = "Show how each segment of the prompt is rendered")]
+    Explain(PromptArgs),
+
+    #[structopt(about = "Measure the latency of the prompt")]
+    Bench(BenchArgs),
+
+    #[structopt(about = "Keep git statuses up to date in the background")]
+    Daemon,
+
+    #[structopt(about = "Manage the cache of segment results")]
+    Cache(CacheArgs),
 }
 
 #[derive(Debug, StructOpt)]
@@ -56,6 +78,14 @@ pub struct PromptArgs {
     #[structopt(help = "Exit status", long = "exit-status", short = "s")]
     pub exit_status: i32,
 
+    #[structopt(
+        help = "Exit statuses of the last pipeline (comma-separated)",
+        long = "pipestatus",
+        default_value = "",
+        hide_default_value = true
+    )]
+    pub pipestatus: Pipestatus,
+
     #[structopt(help = "Number of jobs running", long = "num-jobs", short = "j")]
     pub num_jobs: i32,
 
@@ -64,4 +94,143 @@ pub struct PromptArgs {
 
     #[structopt(help = "Disable git and git_user segment", long = "no-git")]
     pub no_git: bool,
+
+    #[structopt(help = "Print the right prompt", long = "right")]
+    pub right: bool,
+
+    #[structopt(
+        help = "Print the transient prompt",
+        long = "transient",
+        conflicts_with = "right"
+    )]
+    pub transient: bool,
+
+    #[structopt(
+        help = "Print the continuation prompt",
+        long = "continuation",
+        conflicts_with_all = &["right", "transient"]
+    )]
+    pub continuation: bool,
+
+    #[structopt(
+        help = "Print the left, right, transient and continuation prompts, each followed by a NUL",
+        long = "all",
+        conflicts_with_all = &["right", "transient", "continuation"]
+    )]
+    pub all: bool,
+
+    #[structopt(
+        help = "Color mode",
+        long = "color",
+        default_value = "auto",
+        possible_values = POSSIBLE_COLOR_VALUES,
+        case_insensitive = true,
+    )]
+    pub color: ColorMode,
+}
+
+// Comma-separated exit statuses, which may be empty
+#[derive(Debug, Default)]
+pub struct Pipestatus(Vec<i32>);
+
+impl FromStr for Pipestatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Self::default());
+        }
+
+        s.split(',')
+            .map(|status| {
+                status
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid exit status: {}", status))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl Deref for Pipestatus {
+    type Target = [i32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[test]
+fn test_pipestatus_from_str() {
+    assert!("".parse::<Pipestatus>().unwrap().is_empty());
+    assert_eq!(*"0".parse::<Pipestatus>().unwrap(), [0]);
+    assert_eq!(*"0,1,130".parse::<Pipestatus>().unwrap(), [0, 1, 130]);
+    assert!("0,,1".parse::<Pipestatus>().is_err());
+    assert!("x".parse::<Pipestatus>().is_err());
+}
+
+#[derive(Debug, StructOpt)]
+pub struct BenchArgs {
+    #[structopt(
+        help = "Number of iterations",
+        long = "iterations",
+        short = "n",
+        default_value = "100"
+    )]
+    pub iterations: usize,
+
+    #[structopt(help = "Directory to render the prompt in", long = "dir", short = "C")]
+    pub dir: Option<PathBuf>,
+
+    #[structopt(help = "Print the results as JSON", long = "json")]
+    pub json: bool,
+
+    #[structopt(flatten)]
+    pub prompt: PromptArgs,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum PresetArgs {
+    #[structopt(about = "List built-in themes")]
+    List,
+
+    #[structopt(about = "Print a built-in theme")]
+    Show(PresetNameArgs),
+
+    #[structopt(about = "Set the theme of the configuration file")]
+    Apply(PresetNameArgs),
+}
+
+#[derive(Debug, StructOpt)]
+pub struct PresetNameArgs {
+    #[structopt(help = "Theme name")]
+    pub name: String,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum ConfigArgs {
+    #[structopt(about = "Check the configuration files for problems")]
+    Check,
+
+    #[structopt(about = "Print the default configuration")]
+    Default,
+
+    #[structopt(about = "Print the effective configuration")]
+    Show,
+
+    #[structopt(about = "Print the path of the configuration file")]
+    Path,
+
+    #[structopt(about = "Edit the configuration file with $EDITOR")]
+    Edit,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum CacheArgs {
+    #[structopt(about = "Remove every cached result")]
+    Clear,
+
+    #[structopt(about = "Print the path of the cache directory")]
+    Path,
 }
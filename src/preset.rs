@@ -0,0 +1,44 @@
+use anyhow::{anyhow, Result};
+
+use crate::configs::theme::{find_theme, THEMES};
+use crate::configs::Config;
+use crate::opt::PresetArgs;
+
+fn list() -> Result<()> {
+    for (name, _) in THEMES {
+        println!("{}", name);
+    }
+
+    Ok(())
+}
+
+fn show(name: &str) -> Result<()> {
+    let theme = find_theme(name).ok_or_else(|| anyhow!("Unknown theme: {}", name))?;
+    print!("{}", theme);
+
+    Ok(())
+}
+
+fn apply(name: &str) -> Result<()> {
+    find_theme(name).ok_or_else(|| anyhow!("Unknown theme: {}", name))?;
+
+    let config_path = Config::config_path();
+    Config::save_theme(&config_path, name)?;
+
+    println!("Applied {} to {}", name, config_path.display());
+
+    Ok(())
+}
+
+pub fn run(args: &PresetArgs) {
+    let result = match args {
+        PresetArgs::List => list(),
+        PresetArgs::Show(args) => show(&args.name),
+        PresetArgs::Apply(args) => apply(&args.name),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
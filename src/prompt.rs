@@ -1,38 +1,83 @@
-use ansi_term::Color;
+use ansi_term::{Color, Style};
+use anyhow::Result;
+use std::io::{self, Write};
+use std::sync::Arc;
 
-use crate::configs::{Config, SegmentSeparators, SegmentStyle};
-use crate::context::Context;
+use crate::color::ColorDepth;
+use crate::configs::{SegmentSeparators, SegmentStyle};
+use crate::context::{self, Context};
 use crate::opt::PromptArgs;
-use crate::segments;
-use crate::shell::Shell;
+use crate::segments::{self, Segment};
+use crate::table::ignore_broken_pipe;
 
-fn display_content(shell: &Shell, style: &SegmentStyle, content: &str) {
-    // Convert to ansi_term::Style
-    let style = style.foreground.on(style.background);
+fn display_styled(
+    out: &mut dyn Write,
+    context: &Context,
+    style: &Style,
+    text: &str,
+) -> io::Result<()> {
+    let shell = &context.opt.shell;
+
+    if context.color_depth == ColorDepth::None {
+        return write!(out, "{}", shell.escape_content(text));
+    }
+
+    let style = context.color_depth.convert_style(*style);
 
-    print!(
-        "{}{}{} {} {}{}{}",
+    write!(
+        out,
+        "{}{}{}{}{}{}{}",
         shell.control_prefix(),
         style.prefix(),
         shell.control_suffix(),
-        shell.escape_content(content),
+        shell.escape_content(text),
         shell.control_prefix(),
         style.suffix(),
         shell.control_suffix(),
-    );
+    )
+}
+
+fn display_content(
+    out: &mut dyn Write,
+    context: &Context,
+    style: &SegmentStyle,
+    icon: Option<&str>,
+    content: &str,
+) -> io::Result<()> {
+    match icon {
+        Some(icon) if content.is_empty() => display_styled(
+            out,
+            context,
+            &style.icon_ansi_style(),
+            &format!(" {} ", icon),
+        ),
+        Some(icon) => {
+            display_styled(
+                out,
+                context,
+                &style.icon_ansi_style(),
+                &format!(" {}", icon),
+            )?;
+            display_styled(out, context, &style.ansi_style(), &format!(" {} ", content))
+        }
+        None => display_styled(out, context, &style.ansi_style(), &format!(" {} ", content)),
+    }
 }
 
 fn display_separator(
-    shell: &Shell,
+    out: &mut dyn Write,
+    context: &Context,
     next_style: &SegmentStyle,
     prev_bg: Color,
     separators: &SegmentSeparators,
-) {
+) -> io::Result<()> {
     #![allow(clippy::useless_let_if_seq)]
     let style;
     let separator;
 
-    if prev_bg == next_style.background {
+    let depth = context.color_depth;
+
+    if depth.convert_color(prev_bg) == depth.convert_color(next_style.background) {
         style = Color::Fixed(8).on(prev_bg);
         separator = &separators.left_wire;
     } else {
@@ -40,73 +85,218 @@ fn display_separator(
         separator = &separators.left_solid;
     }
 
-    print!(
-        "{}{}{}{}{}{}{}",
-        shell.control_prefix(),
-        style.prefix(),
-        shell.control_suffix(),
-        shell.escape_content(separator),
-        shell.control_prefix(),
-        style.suffix(),
-        shell.control_suffix(),
-    );
+    display_styled(out, context, &style, separator)
 }
 
-fn display_closure(shell: &Shell, last_bg: Color, separators: &SegmentSeparators) {
+fn display_closure(
+    out: &mut dyn Write,
+    context: &Context,
+    last_bg: Color,
+    separators: &SegmentSeparators,
+) -> io::Result<()> {
     // Convert to ansi_term::Style
     let style = last_bg.normal();
 
-    print!(
-        "{}{}{}{}{}{}{}",
-        shell.control_prefix(),
-        style.prefix(),
-        shell.control_suffix(),
-        shell.escape_content(&separators.left_solid),
-        shell.control_prefix(),
-        style.suffix(),
-        shell.control_suffix(),
-    );
+    display_styled(out, context, &style, &separators.left_solid)
 }
 
-pub fn run(args: &PromptArgs) {
-    let config = Config::load_from_file_or_create_default(Config::config_path())
-        .map_err(|err| {
-            eprintln!("{}", err);
-        })
-        .unwrap_or_default();
+fn display_right_separator(
+    out: &mut dyn Write,
+    context: &Context,
+    next_style: &SegmentStyle,
+    prev_bg: Option<Color>,
+    separators: &SegmentSeparators,
+) -> io::Result<()> {
+    let depth = context.color_depth;
 
-    let context = Context::new(args, &config);
-    let shell = &context.opt.shell;
+    let (style, separator) = match prev_bg {
+        Some(prev_bg)
+            if depth.convert_color(prev_bg) == depth.convert_color(next_style.background) =>
+        {
+            (Color::Fixed(8).on(prev_bg), &separators.right_wire)
+        }
+        Some(prev_bg) => (next_style.background.on(prev_bg), &separators.right_solid),
+        None => (next_style.background.normal(), &separators.right_solid),
+    };
+
+    display_styled(out, context, &style, separator)
+}
+
+fn display_line(
+    out: &mut dyn Write,
+    context: &Context,
+    line: Vec<Result<Option<Segment>>>,
+) -> io::Result<()> {
     let separators = &context.config.segment_separators;
 
-    for (i, line) in context.config.segments.iter().enumerate() {
-        if i > 0 {
-            println!();
+    let mut prev_bg: Option<Color> = None;
+
+    for result in line {
+        match result {
+            Ok(Some(segment)) => {
+                if let Some(prev_bg) = prev_bg {
+                    display_separator(out, context, &segment.style, prev_bg, separators)?;
+                }
+
+                display_content(
+                    out,
+                    context,
+                    &segment.style,
+                    segment.icon.as_deref(),
+                    &segment.content,
+                )?;
+                prev_bg = Some(segment.style.background);
+            }
+            Ok(None) => {}
+            Err(error) => {
+                eprintln!("{}", error);
+            }
+        };
+    }
+
+    if let Some(last_bg) = prev_bg {
+        display_closure(out, context, last_bg, separators)?;
+    }
+
+    Ok(())
+}
+
+fn display_right_line(
+    out: &mut dyn Write,
+    context: &Context,
+    line: Vec<Result<Option<Segment>>>,
+) -> io::Result<()> {
+    let separators = &context.config.segment_separators;
+
+    let mut prev_bg: Option<Color> = None;
+
+    for result in line {
+        match result {
+            Ok(Some(segment)) => {
+                display_right_separator(out, context, &segment.style, prev_bg, separators)?;
+                display_content(
+                    out,
+                    context,
+                    &segment.style,
+                    segment.icon.as_deref(),
+                    &segment.content,
+                )?;
+                prev_bg = Some(segment.style.background);
+            }
+            Ok(None) => {}
+            Err(error) => {
+                eprintln!("{}", error);
+            }
+        };
+    }
+
+    Ok(())
+}
+
+enum Prompt<'a> {
+    Left(Vec<&'a [String]>),
+    Right(&'a [String]),
+    // Transient or continuation prompt, which is disabled when it has no segment
+    Optional(&'a [String]),
+}
+
+impl<'a> Prompt<'a> {
+    fn lines(&self) -> Vec<&'a [String]> {
+        match self {
+            Self::Left(lines) => lines.clone(),
+            Self::Right(line) | Self::Optional(line) => vec![line],
         }
+    }
 
-        let mut prev_bg: Option<Color> = None;
+    fn is_disabled(&self) -> bool {
+        matches!(self, Self::Optional(line) if line.is_empty())
+    }
+}
+
+pub fn run(args: PromptArgs) {
+    let config = Arc::new(context::load_config());
+
+    let left = || Prompt::Left(config.segments.iter().map(Vec::as_slice).collect());
+    let right = || Prompt::Right(&config.right_segments);
+    let transient = || Prompt::Optional(&config.transient_segments);
+    let continuation = || Prompt::Optional(&config.continuation_segments);
+
+    // A shell redrawing every prompt asks for all of them at once, so that the
+    // configuration and the repository are loaded only once
+    let prompts = if args.all {
+        vec![left(), right(), transient(), continuation()]
+    } else if args.right {
+        vec![right()]
+    } else if args.transient {
+        vec![transient()]
+    } else if args.continuation {
+        vec![continuation()]
+    } else {
+        vec![left()]
+    };
+
+    if prompts.iter().all(Prompt::is_disabled) {
+        return;
+    }
+
+    let all = args.all;
+    let context = Arc::new(Context::new(Arc::new(args), Arc::clone(&config)));
 
-        for name in line {
-            match segments::build_segment(&context, name) {
-                Ok(Some(segment)) => {
-                    if let Some(prev_bg) = prev_bg {
-                        display_separator(shell, segment.style, prev_bg, separators);
+    // Build the segments of all lines at once
+    let names: Vec<&str> = prompts
+        .iter()
+        .flat_map(Prompt::lines)
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    let results = segments::build_segments(&context, &names)
+        .into_iter()
+        .map(|built| built.result)
+        .collect();
+
+    // The shell may stop reading, as zsh does when a line is accepted before the
+    // asynchronous prompt is ready
+    let stdout = io::stdout();
+    let result = display_prompts(&mut stdout.lock(), &context, &prompts, results, all);
+
+    if let Err(err) = ignore_broken_pipe(result) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+fn display_prompts(
+    out: &mut dyn Write,
+    context: &Context,
+    prompts: &[Prompt],
+    results: Vec<Result<Option<Segment>>>,
+    all: bool,
+) -> io::Result<()> {
+    let mut results = results.into_iter();
+
+    for prompt in prompts {
+        match prompt {
+            Prompt::Right(line) => {
+                display_right_line(out, context, results.by_ref().take(line.len()).collect())?;
+            }
+            _ if prompt.is_disabled() => {}
+            _ => {
+                for (i, line) in prompt.lines().iter().enumerate() {
+                    if i > 0 {
+                        write!(out, "{}", context.opt.shell.line_break())?;
                     }
 
-                    display_content(shell, segment.style, &segment.content);
-                    prev_bg = Some(segment.style.background);
+                    display_line(out, context, results.by_ref().take(line.len()).collect())?;
                 }
-                Ok(None) => {}
-                Err(error) => {
-                    eprintln!("{}", error);
-                }
-            };
+
+                write!(out, " ")?;
+            }
         }
 
-        if let Some(last_bg) = prev_bg {
-            display_closure(shell, last_bg, separators);
+        if all {
+            write!(out, "\0")?;
         }
     }
 
-    print!(" ");
+    out.flush()
 }
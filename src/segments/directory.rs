@@ -298,8 +298,7 @@ fn path_aliases<'a>(
     home_dir: Option<PathBuf>,
     home_symbol: &'a str,
 ) -> Vec<PathAlias<'a>> {
-    let mut result = Vec::new();
-    result.reserve(aliases.len() + 1);
+    let mut result = Vec::with_capacity(aliases.len() + 1);
 
     for a in aliases {
         result.push(PathAlias {
@@ -318,12 +317,12 @@ fn path_aliases<'a>(
     result
 }
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.directory;
 
     let cwd = context.current_dir.as_path();
     let home_dir = dirs::home_dir();
-    let repo_dir = context.git_repo.as_ref().and_then(|repo| repo.workdir());
+    let repo_dir = context.git_workdir.as_deref();
 
     let aliases = path_aliases(&config.aliases, home_dir, &config.home);
 
@@ -341,5 +340,9 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
         &config.error.style
     };
 
-    Some(Segment { style, content })
+    Some(Segment {
+        style: style.clone(),
+        icon: None,
+        content,
+    })
 }
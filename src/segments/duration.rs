@@ -76,14 +76,15 @@ fn test_render_duration() {
     assert_eq!(render_duration(3912.0), "1h 5m 12s");
 }
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.duration;
     let duration = context.opt.duration;
 
     if duration > 0.0 {
         Some(Segment {
-            style: &config.style,
-            content: format!("{}{}", config.icon, render_duration(duration)),
+            style: config.style.clone(),
+            icon: Some(config.icon.clone()).filter(|icon| !icon.is_empty()),
+            content: render_duration(duration),
         })
     } else {
         None
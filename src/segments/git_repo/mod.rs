@@ -1,34 +1,45 @@
 mod status_git2;
 
+pub use status_git2::repo_status;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
 use crate::configs::git_repo::ConfigIcons;
 use crate::context::Context;
+use crate::daemon;
 use crate::segments::Segment;
 use std::cmp::min;
 use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum HeadStatus {
     Branch(String),
     Tag(String),
     Commit(String),
 }
 
+fn head_icon<'a>(head_status: &HeadStatus, icons: &'a ConfigIcons) -> &'a str {
+    match head_status {
+        HeadStatus::Branch(_) => &icons.branch,
+        HeadStatus::Tag(_) => &icons.tag,
+        HeadStatus::Commit(_) => &icons.commit,
+    }
+}
+
 fn build_head_status_text(
     head_status: &HeadStatus,
-    icons: &ConfigIcons,
     display_master: bool,
     commit_hash_len: usize,
 ) -> String {
     match head_status {
         HeadStatus::Branch(name) if !display_master && (name == "master" || name == "main") => {
-            icons.branch.to_string()
-        }
-        HeadStatus::Branch(name) => format!("{} {}", icons.branch, name),
-        HeadStatus::Tag(name) => format!("{} {}", icons.tag, name),
-        HeadStatus::Commit(hash) => {
-            let shorten_hash = &hash[..min(commit_hash_len, hash.len())];
-            format!("{} {}", icons.commit, shorten_hash)
+            String::new()
         }
+        HeadStatus::Branch(name) => name.to_string(),
+        HeadStatus::Tag(name) => name.to_string(),
+        HeadStatus::Commit(hash) => hash[..min(commit_hash_len, hash.len())].to_string(),
     }
 }
 
@@ -59,65 +70,75 @@ fn test_build_head_status_text() {
             status: HeadStatus::Branch("master".to_string()),
             display_master: true,
             commit_hash_len: 7,
-            expected: "B master",
+            expected: "master",
         },
         Scenario {
             status: HeadStatus::Branch("master".to_string()),
             display_master: false,
             commit_hash_len: 7,
-            expected: "B",
+            expected: "",
         },
         Scenario {
             status: HeadStatus::Branch("main".to_string()),
             display_master: false,
             commit_hash_len: 7,
-            expected: "B",
+            expected: "",
         },
         Scenario {
             status: HeadStatus::Branch("some-branch".to_string()),
             display_master: true,
             commit_hash_len: 7,
-            expected: "B some-branch",
+            expected: "some-branch",
         },
         Scenario {
             status: HeadStatus::Branch("some-branch".to_string()),
             display_master: false,
             commit_hash_len: 7,
-            expected: "B some-branch",
+            expected: "some-branch",
         },
         Scenario {
             status: HeadStatus::Tag("some-tag".to_string()),
             display_master: false,
             commit_hash_len: 7,
-            expected: "T some-tag",
+            expected: "some-tag",
         },
         Scenario {
             status: HeadStatus::Commit("0123456789ABCEDF".to_string()),
             display_master: false,
             commit_hash_len: 7,
-            expected: "C 0123456",
+            expected: "0123456",
         },
         Scenario {
             status: HeadStatus::Commit("0123456789ABCEDF".to_string()),
             display_master: false,
             commit_hash_len: 5,
-            expected: "C 01234",
+            expected: "01234",
         },
         Scenario {
             status: HeadStatus::Commit("0123456789ABCEDF".to_string()),
             display_master: false,
             commit_hash_len: 999,
-            expected: "C 0123456789ABCEDF",
+            expected: "0123456789ABCEDF",
         },
     ];
 
     for s in scenarios {
-        let actual = build_head_status_text(&s.status, &icons, s.display_master, s.commit_hash_len);
+        let actual = build_head_status_text(&s.status, s.display_master, s.commit_hash_len);
         assert_eq!(actual, s.expected);
     }
+
+    assert_eq!(
+        head_icon(&HeadStatus::Branch("main".to_string()), &icons),
+        "B"
+    );
+    assert_eq!(head_icon(&HeadStatus::Tag("v1".to_string()), &icons), "T");
+    assert_eq!(
+        head_icon(&HeadStatus::Commit("0123".to_string()), &icons),
+        "C"
+    );
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct WorktreeStatus {
     num_unstaged_new: u32,
     num_unstaged_delete: u32,
@@ -254,7 +275,7 @@ fn test_build_worktree_status_text() {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RemoteStatus {
     commits_behind: u32,
     commits_ahead: u32,
@@ -330,35 +351,97 @@ fn test_build_remote_status_text() {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RepoStatus {
     pub head_status: HeadStatus,
     pub worktree_status: WorktreeStatus,
     pub remote_status: Option<RemoteStatus>,
 }
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+// Linked worktrees share the refs and the config of the main repository
+fn common_dir(git_dir: &Path) -> PathBuf {
+    std::fs::read_to_string(git_dir.join("commondir"))
+        .map(|dir| git_dir.join(dir.trim()))
+        .unwrap_or_else(|_| git_dir.to_path_buf())
+}
+
+// Reference of the upstream branch, which a push or a fetch updates
+fn upstream_ref(common_dir: &Path, branch: &str) -> Option<String> {
+    let config = git2::Config::open(&common_dir.join("config")).ok()?;
+    let remote = config
+        .get_string(&format!("branch.{}.remote", branch))
+        .ok()?;
+    let merge = config
+        .get_string(&format!("branch.{}.merge", branch))
+        .ok()?;
+
+    if remote == "." {
+        return Some(merge);
+    }
+
+    let name = merge.strip_prefix("refs/heads/")?;
+    Some(format!("refs/remotes/{}/{}", remote, name))
+}
+
+fn cache_key(context: &Context, git_dir: &Path) -> Vec<String> {
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).unwrap_or_default();
+    let common_dir = common_dir(git_dir);
+
+    let mut key = vec![
+        context.config.git_repo.display_tag.to_string(),
+        head.trim().to_string(),
+        cache::mtime(git_dir.join("index")),
+        cache::mtime(common_dir.join("packed-refs")),
+        cache::mtime(git_dir.join("FETCH_HEAD")),
+    ];
+
+    if let Some(reference) = head.trim().strip_prefix("ref: ") {
+        key.push(cache::mtime(common_dir.join(reference)));
+
+        if let Some(upstream) = reference
+            .strip_prefix("refs/heads/")
+            .and_then(|branch| upstream_ref(&common_dir, branch))
+        {
+            key.push(cache::mtime(common_dir.join(upstream)));
+        }
+    }
+
+    if let Some(workdir) = &context.git_workdir {
+        key.push(cache::mtime(workdir));
+    }
+
+    key
+}
+
+fn cached_repo_status(context: &Context) -> Option<RepoStatus> {
+    if !context.config.git_repo.cache {
+        return status_git2::git2_repo_status(context);
+    }
+
+    let git_dir = context.git_dir.as_ref()?;
+    let key = cache_key(context, git_dir);
+
+    cache::memoize("git_repo", &git_dir.to_string_lossy(), &key, || {
+        status_git2::git2_repo_status(context)
+    })
+}
+
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.git_repo;
     let icons = &config.icons;
 
-    let status = status_git2::git2_repo_status(context)?;
+    let status = daemon::query_repo_status(context).or_else(|| cached_repo_status(context))?;
 
     // Build content
-    let mut content = String::new();
+    let icon = head_icon(&status.head_status, icons);
 
     let head_text = build_head_status_text(
         &status.head_status,
-        icons,
         config.display_master,
         config.commit_hash_len,
     );
-    content += &head_text;
 
     let worktree_text = build_worktree_status_text(&status.worktree_status, icons);
-    if !worktree_text.is_empty() {
-        content += " ";
-        content += &worktree_text;
-    }
 
     let remote_text = status
         .remote_status
@@ -366,10 +449,13 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
         .map(|s| build_remote_status_text(s, icons))
         .unwrap_or_default();
 
-    if !remote_text.is_empty() {
-        content += " ";
-        content += &remote_text;
-    }
+    let texts = [head_text, worktree_text, remote_text];
+    let content = texts
+        .iter()
+        .filter(|text| !text.is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
 
     let style = if status.worktree_status.is_conflicted() {
         &config.conflicted.style
@@ -381,5 +467,64 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
         &config.clean.style
     };
 
-    Some(Segment { style, content })
+    Some(Segment {
+        style: style.clone(),
+        icon: Some(icon.to_string()),
+        content,
+    })
+}
+
+// Reads HEAD without libgit2, which is fast enough even when the status walk is not
+fn parse_head(head: &str) -> Option<HeadStatus> {
+    let head = head.trim();
+
+    match head.strip_prefix("ref: ") {
+        Some(reference) => reference
+            .strip_prefix("refs/heads/")
+            .map(|branch| HeadStatus::Branch(branch.to_string())),
+        None if !head.is_empty() && head.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Some(HeadStatus::Commit(head.to_string()))
+        }
+        None => None,
+    }
+}
+
+#[test]
+fn test_parse_head() {
+    assert_eq!(
+        parse_head("ref: refs/heads/feature/x\n"),
+        Some(HeadStatus::Branch("feature/x".to_string()))
+    );
+    assert_eq!(
+        parse_head("0123456789abcdef0123456789abcdef01234567\n"),
+        Some(HeadStatus::Commit(
+            "0123456789abcdef0123456789abcdef01234567".to_string()
+        ))
+    );
+    assert_eq!(parse_head("ref: refs/remotes/origin/main"), None);
+    assert_eq!(parse_head(""), None);
+}
+
+pub fn build_placeholder(context: &Context) -> Option<Segment> {
+    let config = &context.config.git_repo;
+
+    let head = std::fs::read_to_string(context.git_dir.as_ref()?.join("HEAD")).ok()?;
+    let head_status = parse_head(&head)?;
+
+    let head_text =
+        build_head_status_text(&head_status, config.display_master, config.commit_hash_len);
+
+    let placeholder = &context.config.timeout_placeholder;
+    let content = if head_text.is_empty() {
+        placeholder.clone()
+    } else {
+        format!("{} {}", head_text, placeholder)
+    };
+
+    // The state of the worktree is unknown, so it must not look clean
+    Some(Segment {
+        style: context.config.timeout_style.clone(),
+        icon: Some(head_icon(&head_status, &config.icons).to_string()),
+        content,
+    })
 }
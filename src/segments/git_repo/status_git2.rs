@@ -107,19 +107,24 @@ fn remote_status(repo: &Repository, head: &Option<Reference>) -> Option<RemoteSt
     })
 }
 
-pub fn git2_repo_status(context: &Context) -> Option<RepoStatus> {
-    let config = &context.config.git_repo;
-
-    let repo = context.git_repo.as_ref()?;
+pub fn repo_status(repo: &Repository, display_tag: bool) -> RepoStatus {
     let head = repo.head().ok();
 
-    let head_status = head_status(repo, &head, config.display_tag);
+    let head_status = head_status(repo, &head, display_tag);
     let worktree_status = worktree_status(repo);
     let remote_status = remote_status(repo, &head);
 
-    Some(RepoStatus {
+    RepoStatus {
         head_status,
         worktree_status,
         remote_status,
-    })
+    }
+}
+
+pub fn git2_repo_status(context: &Context) -> Option<RepoStatus> {
+    let config = &context.config.git_repo;
+
+    let repo = context.open_git_repo()?;
+
+    Some(repo_status(&repo, config.display_tag))
 }
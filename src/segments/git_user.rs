@@ -1,18 +1,47 @@
+use std::path::Path;
+
+use crate::cache;
 use crate::context::Context;
 use crate::segments::Segment;
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
-    let config = &context.config.git_user;
-
-    let repo = context.git_repo.as_ref()?;
+fn user_name(git_dir: &Path) -> Option<String> {
+    let repo = git2::Repository::open(git_dir).ok()?;
     let git_config = repo.config().ok()?;
-    let user = git_config
+
+    git_config
         .get_string("user.name")
         .ok()
-        .filter(|name| !name.is_empty())?;
+        .filter(|name| !name.is_empty())
+}
+
+fn cache_key(git_dir: &Path) -> Vec<String> {
+    let global_configs = [
+        dirs::home_dir().map(|dir| dir.join(".gitconfig")),
+        dirs::config_dir().map(|dir| dir.join("git").join("config")),
+    ];
+
+    std::iter::once(cache::mtime(git_dir.join("config")))
+        .chain(global_configs.iter().flatten().map(cache::mtime))
+        .collect()
+}
+
+pub fn build_segment(context: &Context) -> Option<Segment> {
+    let config = &context.config.git_user;
+
+    let git_dir = context.git_dir.as_ref()?;
+
+    let user = if config.cache {
+        let key = cache_key(git_dir);
+        cache::memoize("git_user", &git_dir.to_string_lossy(), &key, || {
+            user_name(git_dir)
+        })?
+    } else {
+        user_name(git_dir)?
+    };
 
     Some(Segment {
-        style: &config.style,
-        content: format!("{} {}", config.icon, user),
+        style: config.style.clone(),
+        icon: Some(config.icon.clone()),
+        content: user,
     })
 }
@@ -9,18 +9,38 @@ mod time;
 mod user;
 mod venv;
 
+pub use git_repo::{repo_status, RepoStatus};
+
 use anyhow::{anyhow, Result};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::configs::SegmentStyle;
 use crate::context::Context;
 
+pub static SEGMENT_NAMES: &[&str] = &[
+    "os",
+    "shell",
+    "directory",
+    "user",
+    "status",
+    "time",
+    "duration",
+    "git_repo",
+    "git_user",
+    "venv",
+];
+
 #[derive(Debug)]
-pub struct Segment<'a> {
-    pub style: &'a SegmentStyle,
+pub struct Segment {
+    pub style: SegmentStyle,
+    pub icon: Option<String>,
     pub content: String,
 }
 
-pub fn build_segment<'ctx>(context: &'ctx Context, name: &str) -> Result<Option<Segment<'ctx>>> {
+pub fn build_segment(context: &Context, name: &str) -> Result<Option<Segment>> {
     match name {
         "os" => Ok(os::build_segment(context)),
         "shell" => Ok(shell::build_segment(context)),
@@ -35,3 +55,105 @@ pub fn build_segment<'ctx>(context: &'ctx Context, name: &str) -> Result<Option<
         _ => Err(anyhow!("Unknown segment: {}", name)),
     }
 }
+
+pub struct BuiltSegment {
+    pub result: Result<Option<Segment>>,
+    pub elapsed: Duration,
+    pub timed_out: bool,
+}
+
+// Rendered instead of a segment that did not finish in time
+fn build_placeholder(context: &Context, name: &str) -> Option<Segment> {
+    let config = &context.config;
+
+    match name {
+        "git_repo" => git_repo::build_placeholder(context),
+        _ => Some(Segment {
+            style: config.timeout_style.clone(),
+            icon: None,
+            content: config.timeout_placeholder.clone(),
+        }),
+    }
+}
+
+// Builds the segments concurrently and returns them in order. A segment exceeding its
+// timeout is replaced by a placeholder and its thread is left running in the background.
+pub fn build_segments(context: &Arc<Context>, names: &[&str]) -> Vec<BuiltSegment> {
+    let start = Instant::now();
+    let (sender, receiver) = mpsc::channel();
+
+    for (i, name) in names.iter().enumerate() {
+        let context = Arc::clone(context);
+        let name = name.to_string();
+        let sender = sender.clone();
+
+        thread::spawn(move || {
+            // Timed from the start of the thread, so that segments spawned later are not charged
+            // for the time spent spawning the others
+            let start = Instant::now();
+            let result = build_segment(&context, &name);
+            let _ = sender.send((i, result, start.elapsed())); // Ignore error after timeout
+        });
+    }
+    drop(sender);
+
+    let deadlines: Vec<Option<Instant>> = names
+        .iter()
+        .map(|name| context.config.segment_timeout(name).map(|t| start + t))
+        .collect();
+
+    let mut built: Vec<Option<BuiltSegment>> = names.iter().map(|_| None).collect();
+
+    while built.iter().any(Option::is_none) {
+        let next_deadline = built
+            .iter()
+            .zip(&deadlines)
+            .filter(|(built, _)| built.is_none())
+            .filter_map(|(_, deadline)| *deadline)
+            .min();
+
+        let received = match next_deadline {
+            Some(deadline) => receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .map_err(|err| err == RecvTimeoutError::Disconnected),
+            None => receiver.recv().map_err(|_| true),
+        };
+
+        match received {
+            Ok((i, result, elapsed)) => {
+                built[i] = Some(BuiltSegment {
+                    result,
+                    elapsed,
+                    timed_out: false,
+                });
+            }
+            // A thread panicked
+            Err(true) => break,
+            Err(false) => {
+                let now = Instant::now();
+
+                for ((built, deadline), name) in built.iter_mut().zip(&deadlines).zip(names) {
+                    if built.is_none() && deadline.is_some_and(|deadline| deadline <= now) {
+                        *built = Some(BuiltSegment {
+                            result: Ok(build_placeholder(context, name)),
+                            elapsed: now - start,
+                            timed_out: true,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    built
+        .into_iter()
+        .zip(names)
+        .map(|(built, name)| {
+            built.unwrap_or_else(|| BuiltSegment {
+                result: Err(anyhow!("Segment panicked: {}", name)),
+                elapsed: start.elapsed(),
+                timed_out: false,
+            })
+        })
+        .collect()
+}
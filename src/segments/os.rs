@@ -1,7 +1,7 @@
 use crate::context::Context;
 use crate::segments::Segment;
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+pub fn build_segment(context: &Context) -> Option<Segment> {
     #[cfg(target_os = "linux")]
     let config = &context.config.os.linux;
 
@@ -12,7 +12,8 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
     let config = &context.config.os.windows;
 
     Some(Segment {
-        style: &config.style,
-        content: config.icon.clone(),
+        style: config.style.clone(),
+        icon: Some(config.icon.clone()),
+        content: String::new(),
     })
 }
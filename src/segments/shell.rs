@@ -2,22 +2,60 @@ use crate::context::Context;
 use crate::segments::Segment;
 use crate::shell::Shell;
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.shell;
     let shell = &context.opt.shell;
 
     let segment = match shell {
         Shell::Bash => Segment {
-            style: &config.bash.style,
-            content: config.bash.icon.clone(),
+            style: config.bash.style.clone(),
+            icon: Some(config.bash.icon.clone()),
+            content: String::new(),
         },
         Shell::Zsh => Segment {
-            style: &config.zsh.style,
-            content: config.zsh.icon.clone(),
+            style: config.zsh.style.clone(),
+            icon: Some(config.zsh.icon.clone()),
+            content: String::new(),
         },
         Shell::Fish => Segment {
-            style: &config.fish.style,
-            content: config.fish.icon.clone(),
+            style: config.fish.style.clone(),
+            icon: Some(config.fish.icon.clone()),
+            content: String::new(),
+        },
+        Shell::PowerShell => Segment {
+            style: config.powershell.style.clone(),
+            icon: Some(config.powershell.icon.clone()),
+            content: String::new(),
+        },
+        Shell::Nu => Segment {
+            style: config.nu.style.clone(),
+            icon: Some(config.nu.icon.clone()),
+            content: String::new(),
+        },
+        Shell::Elvish => Segment {
+            style: config.elvish.style.clone(),
+            icon: Some(config.elvish.icon.clone()),
+            content: String::new(),
+        },
+        Shell::Xonsh => Segment {
+            style: config.xonsh.style.clone(),
+            icon: Some(config.xonsh.icon.clone()),
+            content: String::new(),
+        },
+        Shell::Tcsh => Segment {
+            style: config.tcsh.style.clone(),
+            icon: Some(config.tcsh.icon.clone()),
+            content: String::new(),
+        },
+        Shell::Ksh => Segment {
+            style: config.ksh.style.clone(),
+            icon: Some(config.ksh.icon.clone()),
+            content: String::new(),
+        },
+        Shell::Mksh => Segment {
+            style: config.mksh.style.clone(),
+            icon: Some(config.mksh.icon.clone()),
+            content: String::new(),
         },
     };
 
@@ -1,3 +1,4 @@
+use crate::configs::status::ConfigFailed;
 use crate::context::Context;
 use crate::segments::Segment;
 use std::fmt::Write as _;
@@ -12,21 +13,122 @@ fn is_root_user() -> bool {
     users::get_current_uid() == 0
 }
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+// Signals whose numbers are the same on Linux, macOS and the BSDs
+fn signal_name(signal: i32) -> Option<&'static str> {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        #[cfg(target_os = "linux")]
+        7 => "SIGBUS",
+        #[cfg(target_os = "linux")]
+        10 => "SIGUSR1",
+        #[cfg(target_os = "linux")]
+        12 => "SIGUSR2",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+fn meaning(exit_status: i32) -> Option<&'static str> {
+    match exit_status {
+        126 => Some("not executable"),
+        127 => Some("not found"),
+        _ => None,
+    }
+}
+
+// Shells report a command killed by a signal as 128 + the signal number
+fn code_text(code: i32, config: &ConfigFailed) -> String {
+    if config.display_signal_name && code > 128 {
+        if let Some(name) = signal_name(code - 128) {
+            return name.to_string();
+        }
+    }
+
+    code.to_string()
+}
+
+// The statuses of a pipeline in which any command failed (e.g. `0|1|0`), or the exit status
+fn exit_status_text(exit_status: i32, pipestatus: &[i32], config: &ConfigFailed) -> String {
+    if pipestatus.len() > 1 && pipestatus.iter().any(|&status| status != 0) {
+        let statuses: Vec<String> = pipestatus
+            .iter()
+            .map(|&status| code_text(status, config))
+            .collect();
+        return statuses.join("|");
+    }
+
+    let mut text = code_text(exit_status, config);
+
+    if config.display_meaning {
+        if let Some(meaning) = meaning(exit_status) {
+            let _ = write!(text, " {}", meaning);
+        }
+    }
+
+    text
+}
+
+#[test]
+fn test_exit_status_text() {
+    let mut config = ConfigFailed::default();
+
+    assert_eq!(exit_status_text(1, &[], &config), "1");
+    assert_eq!(exit_status_text(1, &[1], &config), "1");
+    assert_eq!(exit_status_text(0, &[0, 1, 0], &config), "0|1|0");
+    assert_eq!(exit_status_text(130, &[0, 130], &config), "0|SIGINT");
+    assert_eq!(exit_status_text(0, &[0, 0], &config), "0");
+    assert_eq!(exit_status_text(137, &[], &config), "SIGKILL");
+    assert_eq!(exit_status_text(141, &[141, 0], &config), "SIGPIPE|0");
+    assert_eq!(exit_status_text(127, &[], &config), "127");
+    assert_eq!(exit_status_text(255, &[], &config), "255");
+
+    config.display_meaning = true;
+    assert_eq!(exit_status_text(127, &[], &config), "127 not found");
+    assert_eq!(exit_status_text(126, &[], &config), "126 not executable");
+
+    config.display_signal_name = false;
+    assert_eq!(exit_status_text(130, &[], &config), "130");
+}
+
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.status;
 
+    let exit_status = context.opt.exit_status;
+    let pipestatus: &[i32] = &context.opt.pipestatus;
+    let failed = exit_status != 0 || pipestatus.iter().any(|&status| status != 0);
+
     let style;
+    let icon;
     let mut content = String::new();
 
-    if context.opt.exit_status == 0 {
-        style = &config.succeeded.style;
-        content += &config.icons.succeeded;
+    if !failed {
+        style = config.succeeded.style.clone();
+        icon = &config.icons.succeeded;
     } else {
-        style = &config.failed.style;
-        content += &config.icons.failed;
+        let code = config.codes.get(&exit_status);
+        style = code
+            .and_then(|code| code.style.as_ref())
+            .unwrap_or(&config.failed.style)
+            .clone();
+        icon = code
+            .and_then(|code| code.icon.as_ref())
+            .unwrap_or(&config.icons.failed);
 
         if config.failed.display_exit_status {
-            let _ = write!(content, " {}", context.opt.exit_status);
+            let text = exit_status_text(exit_status, pipestatus, &config.failed);
+            let _ = write!(content, " {}", text);
         }
     }
 
@@ -38,5 +140,9 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
         let _ = write!(content, " {}", config.icons.jobs);
     }
 
-    Some(Segment { style, content })
+    Some(Segment {
+        style,
+        icon: Some(icon.clone()),
+        content: content.trim_start().to_string(),
+    })
 }
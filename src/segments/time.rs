@@ -3,7 +3,7 @@ use chrono::{Local, Utc};
 use crate::context::Context;
 use crate::segments::Segment;
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.time;
 
     let content = if config.utc {
@@ -13,7 +13,8 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
     };
 
     Some(Segment {
-        style: &config.style,
+        style: config.style.clone(),
+        icon: Some(config.icon.clone()).filter(|icon| !icon.is_empty()),
         content,
     })
 }
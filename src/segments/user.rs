@@ -10,7 +10,7 @@ mod users {
     }
 }
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.user;
 
     let username = users::get_current_username();
@@ -32,7 +32,8 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
     };
 
     Some(Segment {
-        style: &config.style,
+        style: config.style.clone(),
+        icon: None,
         content,
     })
 }
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 use crate::context::Context;
 use crate::segments::Segment;
 
-pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
+pub fn build_segment(context: &Context) -> Option<Segment> {
     let config = &context.config.venv;
 
     let venv = env::var_os("VIRTUAL_ENV").map(PathBuf::from)?;
@@ -16,7 +16,8 @@ pub fn build_segment<'ctx>(context: &'ctx Context) -> Option<Segment<'ctx>> {
         .unwrap_or_else(|| Cow::from("?"));
 
     Some(Segment {
-        style: &config.style,
-        content: format!("{} {}", config.icon, env_name),
+        style: config.style.clone(),
+        icon: Some(config.icon.clone()),
+        content: env_name.to_string(),
     })
 }
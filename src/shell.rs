@@ -2,22 +2,60 @@ use structopt::clap::arg_enum;
 
 arg_enum! {
     #[derive(Debug)]
+    #[allow(clippy::enum_variant_names)] // PowerShell is the name of the shell
     pub enum Shell {
         Bash,
         Zsh,
         Fish,
+        PowerShell,
+        Nu,
+        Elvish,
+        Xonsh,
+        Tcsh,
+        Ksh,
+        Mksh,
     }
 }
 
-pub static POSSIBLE_SHELL_VALUES: &[&str] = &["bash", "zsh", "fish"];
+pub static POSSIBLE_SHELL_VALUES: &[&str] = &[
+    "bash",
+    "zsh",
+    "fish",
+    "powershell",
+    "nu",
+    "elvish",
+    "xonsh",
+    "tcsh",
+    "ksh",
+    "mksh",
+];
 
 impl Shell {
     pub fn init_script(&self, asynchronous: bool) -> &'static str {
         match self {
+            Self::Bash if asynchronous => include_str!("init/almel-async.bash"),
             Self::Bash => include_str!("init/almel.bash"),
             Self::Zsh if asynchronous => include_str!("init/almel-async.zsh"),
             Self::Zsh => include_str!("init/almel.zsh"),
+            Self::Fish if asynchronous => include_str!("init/almel-async.fish"),
             Self::Fish => include_str!("init/almel.fish"),
+            // The prompt function has to return the whole prompt, so it cannot be drawn asynchronously
+            Self::PowerShell => include_str!("init/almel.ps1"),
+            Self::Nu => include_str!("init/almel.nu"),
+            // Elvish already updates stale prompts in the background
+            Self::Elvish => include_str!("init/almel.elv"),
+            Self::Xonsh => include_str!("init/almel.xsh"),
+            Self::Tcsh => include_str!("init/almel.tcsh"),
+            Self::Ksh => include_str!("init/almel.ksh"),
+            Self::Mksh => include_str!("init/almel.mksh"),
+        }
+    }
+
+    // Rebinds Enter to draw the transient prompt, so it is added only when one is configured
+    pub fn transient_script(&self) -> Option<&'static str> {
+        match self {
+            Self::Fish => Some(include_str!("init/almel-transient.fish")),
+            _ => None,
         }
     }
 
@@ -26,22 +64,66 @@ impl Shell {
             Self::Bash => content.replace('\\', "\\\\"),
             Self::Zsh => content.replace('%', "%%"),
             Self::Fish => content.into(),
+            // The string returned by the prompt function is written as is
+            Self::PowerShell => content.into(),
+            // Nushell and Elvish interpret raw ANSI sequences in the prompt
+            Self::Nu | Self::Elvish => content.into(),
+            // Xonsh formats the prompt string, expanding fields like {cwd}
+            Self::Xonsh => content.replace('{', "{{").replace('}', "}}"),
+            // Tcsh reads backslash escapes, % sequences and history references in prompt
+            Self::Tcsh => content
+                .replace('\\', "\\\\")
+                .replace('%', "%%")
+                .replace('!', "\\!"),
+            // The value returned by PS1.get is expanded like a double-quoted string
+            Self::Ksh => content
+                .replace('\\', "\\\\")
+                .replace('$', "\\$")
+                .replace('`', "\\`")
+                .replace('!', "!!"),
+            // The output of ${|...;} is not expanded again
+            Self::Mksh => content.replace('!', "!!"),
+        }
+    }
+
+    // tcsh splits the output of a command substitution into words at newlines
+    pub fn line_break(&self) -> &'static str {
+        match self {
+            Self::Tcsh => r"\n",
+            _ => "\n",
         }
     }
 
     pub fn control_prefix(&self) -> &'static str {
         match self {
             Self::Bash => r"\[",
-            Self::Zsh => r"%{",
+            Self::Zsh | Self::Tcsh => r"%{",
             Self::Fish => r"",
+            Self::PowerShell | Self::Nu | Self::Elvish | Self::Xonsh => r"",
+            // Delimiter declared by the init script at the beginning of PS1
+            Self::Ksh | Self::Mksh => "\x01",
         }
     }
 
     pub fn control_suffix(&self) -> &'static str {
         match self {
             Self::Bash => r"\]",
-            Self::Zsh => r"%}",
+            Self::Zsh | Self::Tcsh => r"%}",
             Self::Fish => r"",
+            Self::PowerShell | Self::Nu | Self::Elvish | Self::Xonsh => r"",
+            Self::Ksh | Self::Mksh => "\x01",
         }
     }
 }
+
+#[test]
+fn test_escape_content() {
+    assert_eq!(Shell::Bash.escape_content(r"C:\Users"), r"C:\\Users");
+    assert_eq!(Shell::Zsh.escape_content("100%"), "100%%");
+    assert_eq!(Shell::Xonsh.escape_content("{cwd}"), "{{cwd}}");
+    assert_eq!(Shell::Nu.escape_content("{cwd} 100%"), "{cwd} 100%");
+    assert_eq!(Shell::Tcsh.escape_content("100%!"), r"100%%\!");
+    assert_eq!(Shell::Tcsh.escape_content(r"C:\new"), r"C:\\new");
+    assert_eq!(Shell::Ksh.escape_content(r"$HOME\`!"), r"\$HOME\\\`!!");
+    assert_eq!(Shell::Mksh.escape_content("$HOME!"), "$HOME!!");
+}
@@ -0,0 +1,41 @@
+use std::io::{self, Write};
+
+// Prints rows as left-aligned columns separated by two spaces
+pub fn print_table(header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    let mut print_row = |row: &[&str]| {
+        let columns: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+
+        writeln!(out, "{}", columns.join("  ").trim_end())
+    };
+
+    print_row(header)?;
+    for row in rows {
+        let row: Vec<&str> = row.iter().map(String::as_str).collect();
+        print_row(&row)?;
+    }
+
+    Ok(())
+}
+
+// A closed pipe, as in `almel explain | head`, is not an error
+pub fn ignore_broken_pipe(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}